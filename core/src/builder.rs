@@ -0,0 +1,124 @@
+/* Str2table core crate for building tables
+ * Copyright (C) 2024 Peng Zijun, Xia Tingxuan
+ *
+ * This file is part of Str2table.
+ * Foobar is free software: you can redistribute it and/or modify it under the terms of
+ * the GNU General Public License as published by the Free Software Foundation, either
+ * version 3 of the License, or (at your option) any later version.
+ * Str2table is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+ * without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License along with Foobar.
+ * If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! # Builder
+//! Build a table from plain values in code, without making the lines and cells by hand
+use crate::setting::OutputColor;
+use crate::table::Table;
+use crate::tablecell::Tablecell;
+use crate::tableline::Tableline;
+
+/// A builder of ```Table```, each value is parsed to a cell like the input, e.g.
+/// `TableBuilder::new().header(["name", "age"]).row(["alice", "30"]).build()`
+#[derive(Debug, Default)]
+pub struct TableBuilder {
+    header: Option<Tableline>,
+    rows: Vec<Tableline>,
+    /// The colors to set, `(row, col, color)`
+    colors: Vec<(usize, usize, OutputColor)>,
+}
+
+impl TableBuilder {
+    pub fn new() -> TableBuilder {
+        TableBuilder::default()
+    }
+
+    /// Set the first line of the table, the values are always kept as strings.
+    /// The former header is replaced if it is called again
+    pub fn header<I: IntoIterator<Item = S>, S: Into<String>>(mut self, values: I) -> Self {
+        self.header = Some(Tableline::from_vec(
+            values
+                .into_iter()
+                .map(|value| Tablecell::force_as_string(value.into()))
+                .collect(),
+        ));
+        self
+    }
+
+    /// Add a line to the end of the table, the type of each value is decided automatically
+    pub fn row<I: IntoIterator<Item = S>, S: Into<String>>(mut self, values: I) -> Self {
+        self.rows.push(Tableline::from_vec(
+            values
+                .into_iter()
+                .map(|value| Tablecell::auto_from(value.into()))
+                .collect(),
+        ));
+        self
+    }
+
+    /// Add a line of cells made in code, e.g. by ```Tablecell::int()```, to the end of the table
+    pub fn row_cells<I: IntoIterator<Item = Tablecell>>(mut self, cells: I) -> Self {
+        self.rows
+            .push(Tableline::from_vec(cells.into_iter().collect()));
+        self
+    }
+
+    /// Set the color of a cell, the row counts the header if there is one.
+    /// It is ignored if the cell does not exist when building
+    pub fn with_color(mut self, row: usize, col: usize, color: OutputColor) -> Self {
+        self.colors.push((row, col, color));
+        self
+    }
+
+    /// Build the table
+    pub fn build(self) -> Table {
+        let mut table: Table = self.header.into_iter().chain(self.rows).collect();
+        for (row, col, color) in self.colors {
+            let _ = table.set_color_cell((row, col), color);
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let table = TableBuilder::new()
+            .row(["a", "1"])
+            .row(["2.5", "true"])
+            .build();
+        let parsed = Table::from_string("a 1\n2.5 true".to_string(), " ", "\n");
+        assert!(table == parsed);
+
+        let table = TableBuilder::new()
+            .header(["name", "1"])
+            .row(vec!["x".to_string(), "2".to_string()])
+            .with_color(1, 1, OutputColor::Red)
+            .with_color(5, 5, OutputColor::Red)
+            .build();
+        assert_eq!(table.dimensions(), (2, 2));
+        assert_eq!(
+            format!("{:?}", table.get_line(0).unwrap().get_cell(1).unwrap()),
+            "1<str><Black>"
+        );
+        assert_eq!(
+            format!("{:?}", table.get_line(1).unwrap().get_cell(1).unwrap()),
+            "2<int><Red>"
+        );
+    }
+
+    #[test]
+    fn test_row_cells() {
+        let table = TableBuilder::new()
+            .row_cells([Tablecell::string("007"), Tablecell::int(7)])
+            .build();
+        assert_eq!(
+            format!("{:?}", table.get_line(0).unwrap()),
+            "| 007<str><Black> | 7<int><Black> | "
+        );
+    }
+}
@@ -15,11 +15,15 @@
 //! Error types for Str2table
 pub mod arg_error;
 pub mod conflicts;
+pub mod duplicate_keys;
+pub mod http_error;
+pub mod io_error;
 pub mod keyword_missing;
+pub mod ragged_row;
 pub mod range_error;
 
 /// A enum to describe the level of an error
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub enum ErrorLevel {
     /// Error that can be ignore or fixed automatically
     Warning,
@@ -40,6 +44,17 @@ impl ToString for ErrorLevel {
     }
 }
 
+impl ErrorLevel {
+    /// Get the default exit code of the program when it ends with an error of this level
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ErrorLevel::Warning => 0,
+            ErrorLevel::Error => 1,
+            ErrorLevel::Fatal => 2,
+        }
+    }
+}
+
 /// A trait for Error types in this project
 pub trait ErrorType: std::error::Error {
     /// Get the general description of this Error
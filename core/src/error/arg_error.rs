@@ -31,6 +31,8 @@ pub enum ArgErrorKind {
     WrongFormat,
     Conflicts,
     FormatError,
+    ForceParseFailed,
+    CellTruncated,
 }
 
 impl ToString for ArgErrorKind {
@@ -40,6 +42,8 @@ impl ToString for ArgErrorKind {
             ArgErrorKind::WrongFormat => "WrongFormat".to_string(),
             ArgErrorKind::Conflicts => "Conflicts".to_string(),
             ArgErrorKind::FormatError => "FormatError".to_string(),
+            ArgErrorKind::ForceParseFailed => "ForceParseFailed".to_string(),
+            ArgErrorKind::CellTruncated => "CellTruncated".to_string(),
         }
     }
 }
@@ -51,6 +55,10 @@ impl ArgErrorKind {
             ArgErrorKind::WrongFormat => "The format of this argument is wrong.".to_string(),
             ArgErrorKind::Conflicts => "This argument causes conflict(s)".to_string(),
             ArgErrorKind::FormatError => "This file format is unsupported.".to_string(),
+            ArgErrorKind::ForceParseFailed => {
+                "Some cells couldn't be forced to the given type.".to_string()
+            }
+            ArgErrorKind::CellTruncated => "A cell is longer than the max bytes.".to_string(),
         }
     }
     pub fn get_hint(&self) -> Option<String> {
@@ -61,6 +69,12 @@ impl ArgErrorKind {
             }
             ArgErrorKind::Conflicts => Some("Please check the reason.".to_string()),
             ArgErrorKind::FormatError => Some("Please check the format of the file.".to_string()),
+            ArgErrorKind::ForceParseFailed => {
+                Some("Please check the force parse rule or the input.".to_string())
+            }
+            ArgErrorKind::CellTruncated => {
+                Some("Please check the seperation and quotes of the line.".to_string())
+            }
         }
     }
     pub fn get_level(&self) -> ErrorLevel {
@@ -69,6 +83,8 @@ impl ArgErrorKind {
             ArgErrorKind::WrongFormat => ErrorLevel::Error,
             ArgErrorKind::Conflicts => ErrorLevel::Error,
             ArgErrorKind::FormatError => ErrorLevel::Error,
+            ArgErrorKind::ForceParseFailed => ErrorLevel::Warning,
+            ArgErrorKind::CellTruncated => ErrorLevel::Warning,
         }
     }
 }
@@ -0,0 +1,85 @@
+/* Str2table core crate for error types
+ * Copyright (C) 2024 Peng Zijun
+ *
+ * This file is part of Str2table.
+ * Foobar is free software: you can redistribute it and/or modify it under the terms of
+ * the GNU General Public License as published by the Free Software Foundation, either
+ * version 3 of the License, or (at your option) any later version.
+ * Str2table is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+ * without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License along with Foobar.
+ * If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! DuplicateKeys is a error type that is used to describe a column of the table which has
+//! duplicate values, when the values should be unique.
+use super::{ErrorLevel, ErrorType};
+
+pub struct DuplicateKeys {
+    pub name: String,
+    pub description: String,
+    pub level: ErrorLevel,
+    /// The 1-based number of the column
+    pub column: usize,
+    /// Each duplicated value and the 1-based numbers of the lines where it appears
+    pub duplicates: Vec<(String, Vec<usize>)>,
+    pub hint: Option<String>,
+}
+
+impl DuplicateKeys {
+    pub fn new(column: usize, duplicates: Vec<(String, Vec<usize>)>) -> Self {
+        Self {
+            name: "DuplicateKeys".to_string(),
+            description: "The values of the column are not unique.".to_string(),
+            level: ErrorLevel::Error,
+            column,
+            duplicates,
+            hint: Some("Please check the column of the table, or remove `--unique`.".to_string()),
+        }
+    }
+}
+
+impl ErrorType for DuplicateKeys {
+    fn describe(&self) -> String {
+        self.description.clone()
+    }
+
+    fn level(&self) -> ErrorLevel {
+        self.level
+    }
+
+    fn reason(&self) -> Option<String> {
+        let mut reason = format!("Column {} has duplicate values:", self.column);
+        for (value, lines) in self.duplicates.iter() {
+            reason.push_str(&format!("\n\t\"{}\" in lines {:?}", value, lines));
+        }
+        Some(reason)
+    }
+
+    fn attempt(&self) -> Option<String> {
+        None
+    }
+
+    fn hint(&self) -> Option<String> {
+        self.hint.clone()
+    }
+}
+
+impl std::error::Error for DuplicateKeys {
+    fn description(&self) -> &str {
+        self.description.as_str()
+    }
+}
+
+impl std::fmt::Display for DuplicateKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message(ErrorLevel::Warning))
+    }
+}
+
+impl std::fmt::Debug for DuplicateKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message(ErrorLevel::Warning))
+    }
+}
@@ -0,0 +1,92 @@
+/* Str2table core crate for error types
+ * Copyright (C) 2024 Peng Zijun
+ *
+ * This file is part of Str2table.
+ * Foobar is free software: you can redistribute it and/or modify it under the terms of
+ * the GNU General Public License as published by the Free Software Foundation, either
+ * version 3 of the License, or (at your option) any later version.
+ * Str2table is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+ * without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License along with Foobar.
+ * If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! HttpError is a error type that is used to describe the error when fetching the input
+//! from a url.
+use super::{ErrorLevel, ErrorType};
+
+pub struct HttpError {
+    pub name: String,
+    pub description: String,
+    pub level: ErrorLevel,
+    pub url: String,
+    /// The status code of the response, None if there is no response
+    pub status: Option<u16>,
+    /// What goes wrong, e.g. the error of the connection
+    pub error: String,
+    pub hint: Option<String>,
+}
+
+impl HttpError {
+    pub fn new(url: &str, status: Option<u16>, error: String) -> Self {
+        Self {
+            name: "HttpError".to_string(),
+            description: "Cannot fetch the input from the url.".to_string(),
+            level: ErrorLevel::Fatal,
+            url: url.to_string(),
+            status,
+            error,
+            hint: Some("Please check the url and the network.".to_string()),
+        }
+    }
+}
+
+impl ErrorType for HttpError {
+    fn describe(&self) -> String {
+        self.description.clone()
+    }
+
+    fn level(&self) -> ErrorLevel {
+        self.level
+    }
+
+    fn reason(&self) -> Option<String> {
+        match self.status {
+            Some(status) => Some(format!(
+                "The server of \"{}\" responds with status {}: {}.",
+                self.url, status, self.error
+            )),
+            None => Some(format!(
+                "Error happens in \"{}\", where {}.",
+                self.url, self.error
+            )),
+        }
+    }
+
+    fn attempt(&self) -> Option<String> {
+        None
+    }
+
+    fn hint(&self) -> Option<String> {
+        self.hint.clone()
+    }
+}
+
+impl std::error::Error for HttpError {
+    fn description(&self) -> &str {
+        self.description.as_str()
+    }
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message(ErrorLevel::Warning))
+    }
+}
+
+impl std::fmt::Debug for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message(ErrorLevel::Warning))
+    }
+}
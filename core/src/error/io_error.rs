@@ -0,0 +1,81 @@
+/* Str2table core crate for error types
+ * Copyright (C) 2024 Peng Zijun
+ *
+ * This file is part of Str2table.
+ * Foobar is free software: you can redistribute it and/or modify it under the terms of
+ * the GNU General Public License as published by the Free Software Foundation, either
+ * version 3 of the License, or (at your option) any later version.
+ * Str2table is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+ * without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License along with Foobar.
+ * If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! IoError is a error type that is used to describe the error when reading or writing a file.
+use super::{ErrorLevel, ErrorType};
+
+pub struct IoError {
+    pub name: String,
+    pub description: String,
+    pub level: ErrorLevel,
+    pub file: String,
+    pub error: std::io::Error,
+    pub hint: Option<String>,
+}
+
+impl IoError {
+    pub fn new(file: &str, error: std::io::Error) -> Self {
+        Self {
+            name: "IoError".to_string(),
+            description: "Cannot read or write the file.".to_string(),
+            level: ErrorLevel::Fatal,
+            file: file.to_string(),
+            error,
+            hint: Some("Please check the path and the permission of the file.".to_string()),
+        }
+    }
+}
+
+impl ErrorType for IoError {
+    fn describe(&self) -> String {
+        self.description.clone()
+    }
+
+    fn level(&self) -> ErrorLevel {
+        self.level
+    }
+
+    fn reason(&self) -> Option<String> {
+        Some(format!(
+            "Error happens in \"{}\", where {}.",
+            self.file, self.error
+        ))
+    }
+
+    fn attempt(&self) -> Option<String> {
+        None
+    }
+
+    fn hint(&self) -> Option<String> {
+        self.hint.clone()
+    }
+}
+
+impl std::error::Error for IoError {
+    fn description(&self) -> &str {
+        self.description.as_str()
+    }
+}
+
+impl std::fmt::Display for IoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message(ErrorLevel::Warning))
+    }
+}
+
+impl std::fmt::Debug for IoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message(ErrorLevel::Warning))
+    }
+}
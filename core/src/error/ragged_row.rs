@@ -0,0 +1,90 @@
+/* Str2table core crate for error types
+ * Copyright (C) 2024 Peng Zijun
+ *
+ * This file is part of Str2table.
+ * Foobar is free software: you can redistribute it and/or modify it under the terms of
+ * the GNU General Public License as published by the Free Software Foundation, either
+ * version 3 of the License, or (at your option) any later version.
+ * Str2table is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+ * without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License along with Foobar.
+ * If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! RaggedRow is a error type that is used to describe a line of the table which has a
+//! different number of cells from the first line, when the input should be rectangular.
+use super::{ErrorLevel, ErrorType};
+
+pub struct RaggedRow {
+    pub name: String,
+    pub description: String,
+    pub level: ErrorLevel,
+    /// The 1-based number of the line
+    pub line: usize,
+    /// The number of cells in the line
+    pub count: usize,
+    /// The number of cells in the first line
+    pub expected: usize,
+    pub hint: Option<String>,
+}
+
+impl RaggedRow {
+    pub fn new(line: usize, count: usize, expected: usize) -> Self {
+        Self {
+            name: "RaggedRow".to_string(),
+            description: "The lines of the table have different numbers of cells.".to_string(),
+            level: ErrorLevel::Error,
+            line,
+            count,
+            expected,
+            hint: Some(
+                "Please check the seperation of the line, or remove `--strict-rectangular`."
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+impl ErrorType for RaggedRow {
+    fn describe(&self) -> String {
+        self.description.clone()
+    }
+
+    fn level(&self) -> ErrorLevel {
+        self.level
+    }
+
+    fn reason(&self) -> Option<String> {
+        Some(format!(
+            "Line {} has {} cells, but the first line has {}.",
+            self.line, self.count, self.expected
+        ))
+    }
+
+    fn attempt(&self) -> Option<String> {
+        None
+    }
+
+    fn hint(&self) -> Option<String> {
+        self.hint.clone()
+    }
+}
+
+impl std::error::Error for RaggedRow {
+    fn description(&self) -> &str {
+        self.description.as_str()
+    }
+}
+
+impl std::fmt::Display for RaggedRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message(ErrorLevel::Warning))
+    }
+}
+
+impl std::fmt::Debug for RaggedRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message(ErrorLevel::Warning))
+    }
+}
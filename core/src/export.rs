@@ -25,6 +25,6 @@ use xlsxwriter::prelude::*;
 pub trait Export {
     fn to_console(&self);
     fn to_txt(&self, file: &str, seperation: char) -> Result<(), std::io::Error>;
-    // fn to_csv(&self, file: &str) -> Result<(), String>;
+    fn to_csv(&self, file: &str) -> Result<(), std::io::Error>;
     fn to_excel(&self, file: &str) -> Result<(), XlsxError>;
 }
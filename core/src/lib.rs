@@ -12,10 +12,17 @@
  * If not, see <https://www.gnu.org/licenses/>.
  */
 
+pub mod builder;
 pub mod error;
 pub mod export;
 pub mod setting;
+pub mod sniff;
+pub mod stats;
 pub mod table;
 mod tablecell;
 mod tablecellcore;
 mod tableline;
+
+pub use tablecell::Tablecell;
+pub use tablecellcore::Tablecellcore;
+pub use tableline::Tableline;
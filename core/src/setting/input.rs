@@ -13,6 +13,7 @@
  */
 
 use super::core::LineColumn;
+use super::output::{validate_float_format, ColorChoice, FloatFormat};
 use crate::error::arg_error::{ArgError, ArgErrorKind};
 use crate::error::conflicts::Conflicts;
 use crate::error::keyword_missing::KeywordMissing;
@@ -24,20 +25,34 @@ use once_cell::sync::Lazy;
 use regex::{Regex, RegexSet};
 use std::str::FromStr;
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+/// A enum to specify the format of the input, `Ssv` is split by the seperation and the end of
+/// line, `Csv` is RFC 4180 csv with quoted fields, `Tsv` is tab seperated without quoting
+pub enum InputFormat {
+    #[default]
+    Ssv,
+    Csv,
+    Tsv,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
-/// A enum to specify the parse mode, `A` represents auto, `S` represents force to string.
+/// A enum to specify the parse mode, `A` represents auto, `S` represents force to string,
+/// `N` represents force to string but the empty cells are kept as missing values (null).
 pub enum ParseMode {
     A,
     S,
+    N,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 /// A enum to specify the force type, `S` represents string, `I` represents integer,
-/// `F` represents float.
+/// `F` represents float, `B` represents bool, `R` represents rational.
 pub enum ForceType {
     S,
     I,
     F,
+    B,
+    R,
 }
 
 impl FromStr for ForceType {
@@ -47,46 +62,284 @@ impl FromStr for ForceType {
             "s" => Ok(ForceType::S),
             "i" => Ok(ForceType::I),
             "f" => Ok(ForceType::F),
+            "b" => Ok(ForceType::B),
+            "r" => Ok(ForceType::R),
             "S" => Ok(ForceType::S),
             "I" => Ok(ForceType::I),
             "F" => Ok(ForceType::F),
+            "B" => Ok(ForceType::B),
+            "R" => Ok(ForceType::R),
             _ => Err(()),
         }
     }
 }
 
+impl ForceType {
+    /// Get the name of the type, the same as the type name of a cell of this type
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ForceType::S => "str",
+            ForceType::I => "int",
+            ForceType::F => "float",
+            ForceType::B => "bool",
+            ForceType::R => "rational",
+        }
+    }
+}
+
 /// Commandline args
+///
+/// Some args fall back to an environment variable, a flag in the commandline is used first,
+/// then the environment variable, then the default
 #[derive(Debug, PartialEq, Parser)]
 pub struct InputArgs {
     #[arg(short, long, value_hint = clap::ValueHint::FilePath)]
     /// The path of input file, use console input if not set
     pub input: Option<std::path::PathBuf>,
 
-    #[arg(short, long, default_value = " ")]
+    #[arg(long, conflicts_with = "input", value_hint = clap::ValueHint::Url)]
+    /// Fetch the input from a url, needs the `http` feature. The seperation is `,` for a
+    /// `.csv` url and a tab for a `.tsv` url, unless another seperation is given
+    pub url: Option<String>,
+
+    #[arg(short, long, default_value = " ", env = "STR2TABLE_SEP")]
     /// Set the seperation pattern of the table, default is ` `, can be multiple chars
     pub seperation: String,
 
+    #[arg(long, default_value = "ssv", value_enum)]
+    /// The format of the input. The seperation, end of line and parse mode are only used
+    /// by `ssv`, `csv` and `tsv` keep empty fields and never trim them
+    pub input_format: InputFormat,
+
     #[arg(short, long, default_value = "\n")]
     /// Set the pattern to end the line, default is `\n`. if this is not `\n`,
     /// then all the `\n` and `\r` in the input will be removed first.
     pub end_line: String,
 
-    #[arg(short, long, default_value = "a", value_enum)]
+    #[arg(
+        short,
+        long,
+        default_value = "a",
+        value_enum,
+        env = "STR2TABLE_PARSE_MODE"
+    )]
     pub parse_mode: ParseMode,
 
     #[arg(short, long, value_parser = validate_force_parse)]
     /// Give the lines or columns with specific type.
+    /// Lines and columns are 1-based, e.g. `1-2li` forces the first two lines to int
     pub force_parse: Option<(Vec<(usize, ForceType)>, super::LineColumn)>,
+
+    #[arg(short, long, value_parser = validate_unique)]
+    /// Use a number end with `c` to specify a column that should have no duplicate values,
+    /// abort with the duplicated values if it has
+    pub unique: Option<usize>,
+
+    #[arg(long)]
+    /// Insert a column of 1-based line numbers at the front of the table,
+    /// which is part of the data and will also be exported
+    pub row_numbers: bool,
+
+    #[arg(long, default_value = "auto", value_parser = validate_float_format)]
+    /// How floats are shown in console, `auto`, `sci` (e.g. `1.5e-20`) or the number of
+    /// digits after the decimal point
+    pub float_format: FloatFormat,
+
+    #[arg(long, default_value = "auto", value_enum)]
+    /// Whether colors are shown in console, `auto` shows them only if the output is a terminal
+    pub color: ColorChoice,
+
+    #[arg(long, value_parser = validate_error_exit_code)]
+    /// Change the exit code of an error level, e.g. `fatal=3`, can be used multiple times.
+    /// By default warning is 0, error is 1 and fatal is 2
+    pub error_exit_code: Vec<(ErrorLevel, u8)>,
+
+    #[arg(long)]
+    /// Abort if a line has a different number of cells from the first line
+    pub strict_rectangular: bool,
+
+    #[arg(long)]
+    /// Guess the seperation, quoting and header of the input and print them,
+    /// without printing the table
+    pub sniff: bool,
+
+    #[arg(long)]
+    /// Print the number of rows and columns, and the types of cells and the width of the
+    /// widest cell of each column, without printing the table
+    pub stats: bool,
+
+    #[arg(long, hide = true)]
+    /// Print every parsed cell as a json line to stderr, for bug reports
+    pub debug_dump: bool,
+
+    #[command(flatten)]
+    pub parse_options: ParseOptions,
 }
 
 impl Default for InputArgs {
     fn default() -> Self {
         InputArgs {
             input: None,
+            url: None,
             seperation: " ".to_string(),
+            input_format: InputFormat::default(),
             end_line: "\n".to_string(),
             parse_mode: ParseMode::A,
             force_parse: None,
+            unique: None,
+            row_numbers: false,
+            float_format: FloatFormat::default(),
+            color: ColorChoice::Auto,
+            error_exit_code: Vec::new(),
+            strict_rectangular: false,
+            sniff: false,
+            stats: false,
+            debug_dump: false,
+            parse_options: ParseOptions::default(),
+        }
+    }
+}
+
+/// Options that control how a string is parsed to cells
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    #[arg(long, default_value_t = 4096)]
+    /// Values longer than this are kept as string instead of being parsed as number,
+    /// which avoids huge memory use on pathological input
+    pub max_int_digits: usize,
+
+    #[arg(long)]
+    /// Truncate any cell longer than this many bytes while parsing, with a warning
+    pub cell_max_bytes: Option<usize>,
+
+    #[arg(long = "keep-empty-fields", action = ArgAction::SetFalse)]
+    /// Keep the empty fields between consecutive seperations, e.g. `a,,b` is three cells
+    /// with an empty one in the middle instead of two cells.
+    /// The empty fields are collapsed by default
+    pub collapse_empty: bool,
+
+    #[arg(long = "no-trim", action = ArgAction::SetFalse)]
+    /// Keep the blank around each cell, e.g. ` a ` keeps its spaces instead of being the cell `a`.
+    /// The blank is trimmed by default
+    pub trim: bool,
+
+    #[arg(long)]
+    /// Allow cells wrapped in double quotes to contain the seperation, `""` inside the
+    /// quotes is an escaped quote. The quotes are stripped from the cell
+    pub quoted: bool,
+
+    #[arg(long)]
+    /// Allow a backslash to escape the seperation, e.g. `a\,b` is the cell `a,b`,
+    /// and `\\` is a literal backslash
+    pub escape: bool,
+
+    #[arg(long)]
+    /// Lines starting with this prefix (ignoring the blank before it) are skipped, e.g. `#`
+    pub comment_prefix: Option<String>,
+
+    #[arg(long)]
+    /// Treat the seperation as a regex, e.g. `\s+` for one or more blanks
+    pub regex_seperation: bool,
+
+    #[arg(long)]
+    /// Strip this currency symbol before or after a number, e.g. `$` for `$1,234`
+    pub currency_symbol: Option<String>,
+
+    #[arg(long)]
+    /// Strip this digit grouping seperator in a number, e.g. `,` for `1,234`
+    pub group_seperator: Option<char>,
+
+    #[arg(long, default_value_t = '.')]
+    /// The decimal point of numbers, e.g. `,` for `1 234,56`
+    pub decimal_seperator: char,
+
+    #[arg(long)]
+    /// Parse numbers ending with `%` as percentages, e.g. `50%` is stored as 0.5
+    /// and still shown as `50%`
+    pub percent: bool,
+
+    #[arg(long)]
+    /// Parse numbers like `3/4` as rationals, which are reduced, e.g. `6/8` is `3/4`.
+    /// Without it they are kept as string, since they are often dates or scores
+    pub rational: bool,
+
+    #[arg(long)]
+    /// Keep numbers with leading zeros like `007` as string, e.g. for ids.
+    /// `0`, `0.5` and `0x10` are still numbers
+    pub preserve_leading_zeros: bool,
+
+    #[arg(long)]
+    /// Parse empty and blank cells as missing values (null) instead of empty strings,
+    /// use with `--keep-empty-fields` to keep the empty cells
+    pub empty_as_null: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_int_digits: 4096,
+            cell_max_bytes: None,
+            collapse_empty: true,
+            trim: true,
+            quoted: false,
+            escape: false,
+            comment_prefix: None,
+            regex_seperation: false,
+            currency_symbol: None,
+            group_seperator: None,
+            decimal_seperator: '.',
+            percent: false,
+            rational: false,
+            preserve_leading_zeros: false,
+            empty_as_null: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Remove the currency symbol and the grouping seperator from a number and use `.` as its
+    /// decimal point, e.g. `$1,234.5` to `1234.5`, so that it can be parsed as number.
+    ///
+    /// Return None if none of these options is set or the value does not look like a number
+    pub fn strip_number_format(&self, value: &str) -> Option<String> {
+        if self.currency_symbol.is_none()
+            && self.group_seperator.is_none()
+            && self.decimal_seperator == '.'
+        {
+            return None;
+        }
+        let (sign, mut number) = match value.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", value),
+        };
+        if let Some(symbol) = self.currency_symbol.as_deref().filter(|s| !s.is_empty()) {
+            number = number
+                .strip_prefix(symbol)
+                .or_else(|| number.strip_suffix(symbol))
+                .unwrap_or(number)
+                .trim();
+        }
+        let mut number: String = number
+            .chars()
+            .filter(|c| Some(*c) != self.group_seperator)
+            .collect();
+        if self.decimal_seperator != '.' {
+            if number.contains('.') {
+                return None;
+            }
+            number = number.replace(self.decimal_seperator, ".");
+        }
+        if !number.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        Some(format!("{}{}", sign, number))
+    }
+
+    /// Check if the line is a comment line with `comment_prefix`
+    pub fn is_comment(&self, line: &str) -> bool {
+        match &self.comment_prefix {
+            Some(prefix) if !prefix.is_empty() => line.trim_start().starts_with(prefix.as_str()),
+            _ => false,
         }
     }
 }
@@ -95,25 +348,25 @@ fn validate_force_parse(s: &str) -> Result<(Vec<(usize, ForceType)>, super::Line
     // regex to check if input is valid
     let regex_set = RegexSet::new(&[
         // 0. correct format with a ragne
-        r"^[0-9]+-[0-9]+[lcLC][sifSIF]$",
+        r"^[0-9]+-[0-9]+[lcLC][sifbrSIFBR]$",
         // 1. correct format with a single number
-        r"^[0-9]+[lcLC][sifSIF]$",
+        r"^[0-9]+[lcLC][sifbrSIFBR]$",
         // 2. wrong format with a wrong right side
-        r"^[0-9]+-.*[lcLC][sifSIF]$",
+        r"^[0-9]+-.*[lcLC][sifbrSIFBR]$",
         // 3. wrong format with a wrong left side
-        r"^.*-[0-9]+[lcLC][sifSIF]$",
+        r"^.*-[0-9]+[lcLC][sifbrSIFBR]$",
         // 4. wrong format with both side wrong
-        r"^.*-.*[lcLC][sifSIF]$",
+        r"^.*-.*[lcLC][sifbrSIFBR]$",
         // 5. wrong format with wrong number (single)
-        r"^.*[lcLC][sifSIF]$",
+        r"^.*[lcLC][sifbrSIFBR]$",
         // 6. wrong format with wrong type (range)
         r"^[0-9]+-[0-9]+[lcLC].*$",
         // 7. wrong format with wrong type (single)
         r"^[0-9]+[lcLC].*$",
         // 8. wrong format with wrong line/column (range)
-        r"^[0-9]+-[0-9]+.*[sifSIF]$",
+        r"^[0-9]+-[0-9]+.*[sifbrSIFBR]$",
         // 9. wrong format with wrong line/column (single)
-        r"^[0-9]+.*[sifSIF]$",
+        r"^[0-9]+.*[sifbrSIFBR]$",
     ])
     .unwrap();
 
@@ -295,6 +548,80 @@ fn validate_force_parse(s: &str) -> Result<(Vec<(usize, ForceType)>, super::Line
     }
 }
 
+fn validate_unique(s: &str) -> Result<usize, Box<ArgError>> {
+    // regex to check if input is valid
+    let regex_set = RegexSet::new(&[
+        // 0. correct format
+        r"^[0-9]+[cC]$",
+        // 1. wrong format with wrong column
+        r"^[0-9]+.*$",
+        // 2. wrong format with wrong number
+        r"^.*[cC]$",
+    ])
+    .unwrap();
+
+    let matches = regex_set.matches(s).into_iter().collect::<Vec<_>>();
+    if matches.is_empty() {
+        Err(Box::new(ArgError::new(
+            ArgErrorKind::WrongFormat,
+            Some("There is more than one error in this part.".to_string()),
+            Some(s.to_string()),
+            Some(s.to_string()),
+            Some((0, s.len())),
+            None,
+        )))
+    } else if matches[0] == 0 {
+        let num = s[..s.len() - 1].parse::<usize>().unwrap();
+        if num == 0 {
+            let range_error = RangeError::new(
+                RangeErrorKind::OutOfRange,
+                Some(s.to_string()),
+                Some(s.to_string()),
+                Some((0, s.len())),
+            );
+            return Err(Box::new(ArgError::new(
+                ArgErrorKind::WrongFormat,
+                Some(range_error.message(ErrorLevel::Warning).to_string()),
+                Some(s.to_string()),
+                Some(s.to_string()),
+                Some((0, s.len())),
+                None,
+            )));
+        }
+        Ok(num)
+    } else if matches[0] == 1 {
+        let keyword_missing = KeywordMissing::new(
+            Some(s.to_string()),
+            Some(s.to_string()),
+            Some((0, s.len())),
+            "column".to_string(),
+        );
+        Err(Box::new(ArgError::new(
+            ArgErrorKind::WrongFormat,
+            Some(keyword_missing.message(ErrorLevel::Warning).to_string()),
+            Some(s.to_string()),
+            Some(s.to_string()),
+            Some((0, s.len())),
+            None,
+        )))
+    } else {
+        let range_error = RangeError::new(
+            RangeErrorKind::SingleNumberError,
+            Some(s.to_string()),
+            Some(s.to_string()),
+            Some((0, s.len())),
+        );
+        Err(Box::new(ArgError::new(
+            ArgErrorKind::WrongFormat,
+            Some(range_error.message(ErrorLevel::Warning).to_string()),
+            Some(s.to_string()),
+            Some(s.to_string()),
+            Some((0, s.len())),
+            None,
+        )))
+    }
+}
+
 fn parse_single_force(
     part: &str,
     whole_arg: &str,
@@ -302,7 +629,7 @@ fn parse_single_force(
     linecolumn: Option<LineColumn>,
 ) -> Result<(usize, LineColumn, ForceType), ArgError> {
     static RE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"^(?<start>[0-9]+)(?<lc>[lcLC])(?<type>[sifSIF])$").unwrap());
+        Lazy::new(|| Regex::new(r"^(?<start>[0-9]+)(?<lc>[lcLC])(?<type>[sifbrSIFBR])$").unwrap());
     let caps = RE.captures(part).unwrap();
     let start = caps["start"].parse::<usize>().unwrap();
     let lc = LineColumn::from_str(&caps["lc"]).unwrap();
@@ -335,7 +662,7 @@ fn parse_range_force(
     linecolumn: Option<LineColumn>,
 ) -> Result<((usize, usize), LineColumn, ForceType), ArgError> {
     static RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r"^(?<start>[0-9]+)-(?<end>[0-9]+)(?<lc>[lcLC])(?<type>[sifSIF])$").unwrap()
+        Regex::new(r"^(?<start>[0-9]+)-(?<end>[0-9]+)(?<lc>[lcLC])(?<type>[sifbrSIFBR])$").unwrap()
     });
     let caps = RE.captures(part).unwrap();
     let start = caps["start"].parse::<usize>().unwrap();
@@ -358,17 +685,86 @@ fn parse_range_force(
             Some(location),
             None,
         ))
+    } else if start > end {
+        // unlike export color and subtable, a descending range is not allowed in force parse
+        let range_error = RangeError::new(
+            RangeErrorKind::OutOfRange,
+            Some(part.to_string()),
+            Some(whole_arg.to_string()),
+            Some(location),
+        );
+        Err(ArgError::new(
+            ArgErrorKind::WrongFormat,
+            Some(range_error.message(ErrorLevel::Warning).to_string()),
+            Some(part.to_string()),
+            Some(whole_arg.to_string()),
+            Some(location),
+            Some("The start of the range should not be greater than the end.".to_string()),
+        ))
     } else {
         Ok(((start, end), lc, force_type))
     }
 }
 
+fn validate_error_exit_code(s: &str) -> Result<(ErrorLevel, u8), Box<ArgError>> {
+    let wrong_format = |reason: &str, error_arg: &str| {
+        Box::new(ArgError::new(
+            ArgErrorKind::WrongFormat,
+            Some(reason.to_string()),
+            Some(error_arg.to_string()),
+            Some(s.to_string()),
+            s.find(error_arg)
+                .map(|start| (start, start + error_arg.len())),
+            None,
+        ))
+    };
+    let Some((level, code)) = s.split_once('=') else {
+        return Err(wrong_format("the \"=\" is missing.", s));
+    };
+    let level = match level.trim().to_lowercase().as_str() {
+        "warning" => ErrorLevel::Warning,
+        "error" => ErrorLevel::Error,
+        "fatal" => ErrorLevel::Fatal,
+        _ => {
+            return Err(wrong_format(
+                "the level should be one of warning, error and fatal.",
+                level,
+            ))
+        }
+    };
+    match code.trim().parse::<u8>() {
+        Ok(code) => Ok((level, code)),
+        Err(_) => Err(wrong_format(
+            "the exit code should be a number from 0 to 255.",
+            code,
+        )),
+    }
+}
+
 /* ---------------------------------- test ---------------------------------- */
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_env_fallback() {
+        std::env::set_var("STR2TABLE_SEP", ",");
+        std::env::set_var("STR2TABLE_PARSE_MODE", "s");
+        let args = InputArgs::try_parse_from(["str2table"]).unwrap();
+        assert_eq!(args.seperation, ",");
+        assert_eq!(args.parse_mode, ParseMode::S);
+        // the commandline wins
+        let args = InputArgs::try_parse_from(["str2table", "-s", ";", "-p", "a"]).unwrap();
+        assert_eq!(args.seperation, ";");
+        assert_eq!(args.parse_mode, ParseMode::A);
+        std::env::remove_var("STR2TABLE_SEP");
+        std::env::remove_var("STR2TABLE_PARSE_MODE");
+        let args = InputArgs::try_parse_from(["str2table"]).unwrap();
+        assert_eq!(args.seperation, " ");
+        assert_eq!(args.parse_mode, ParseMode::A);
+    }
+
     #[test]
     fn test_validate_force_parse() {
         let true_res = (
@@ -391,13 +787,40 @@ mod tests {
         let result = validate_force_parse("1-3LS,7Li,9-11lF").unwrap();
         assert_eq!(result, true_res);
 
+        // rational type
+        let result = validate_force_parse("2cr,3CR").unwrap();
+        assert_eq!(
+            result,
+            (
+                vec![(2, ForceType::R), (3, ForceType::R)],
+                super::super::LineColumn::Column
+            )
+        );
+
+        // bool type
+        let result = validate_force_parse("1-2lb,4LB").unwrap();
+        assert_eq!(
+            result,
+            (
+                vec![(1, ForceType::B), (2, ForceType::B), (4, ForceType::B)],
+                super::super::LineColumn::Line
+            )
+        );
+
         // different order
         let result = validate_force_parse("9-11lf,7li,1-3ls").unwrap();
         assert_eq!(result, true_res);
 
-        // invalid range (as a FEATURE)
-        let result = validate_force_parse("1-3ls,20-10ls,9-11lf,7li").unwrap();
-        assert_eq!(result, true_res);
+        // descending range is not allowed in force parse
+        let result = validate_force_parse("1-3ls,20-10ls,9-11lf,7li");
+        assert!(result.is_err());
+        let result = validate_force_parse("5-2li");
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(error
+            .reason()
+            .unwrap()
+            .contains(RangeErrorKind::OutOfRange.get_reason().as_str()));
 
         let lc_missing = KeywordMissing::new(None, None, None, "line or column".to_string());
 
@@ -515,4 +938,53 @@ mod tests {
             .unwrap()
             .contains("There is more than one error in this part"));
     }
+
+    #[test]
+    fn test_validate_error_exit_code() {
+        assert_eq!(
+            validate_error_exit_code("fatal=3").unwrap(),
+            (ErrorLevel::Fatal, 3)
+        );
+        assert_eq!(
+            validate_error_exit_code("Error=10").unwrap(),
+            (ErrorLevel::Error, 10)
+        );
+        assert!(validate_error_exit_code("fatal").is_err());
+        assert!(validate_error_exit_code("panic=3").is_err());
+        assert!(validate_error_exit_code("fatal=256").is_err());
+    }
+
+    #[test]
+    fn test_validate_unique() {
+        assert_eq!(validate_unique("1c").unwrap(), 1);
+        assert_eq!(validate_unique("12C").unwrap(), 12);
+
+        // column is 1-based
+        let result = validate_unique("0c");
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .reason()
+            .unwrap()
+            .contains(RangeErrorKind::OutOfRange.get_reason().as_str()));
+
+        // missing column
+        let column_missing = KeywordMissing::new(None, None, None, "column".to_string());
+        let result = validate_unique("1l");
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .reason()
+            .unwrap()
+            .contains(column_missing.describe().as_str()));
+
+        // missing number
+        let result = validate_unique("c");
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .reason()
+            .unwrap()
+            .contains(RangeErrorKind::SingleNumberError.get_reason().as_str()));
+    }
 }
@@ -27,15 +27,20 @@ use regex::{Regex, RegexSet};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 /// A enum to specify the output format,
-/// `Csv` represents csv file, `Txt` represents txt file, `Exls` represents excel file.
+/// `Csv` represents csv file, `Txt` represents txt file, `Exls` represents excel file,
+/// `Svg` represents svg image, `Html` represents html table.
 pub enum OutputFormat {
     Csv,
     Txt,
     Exls,
+    Svg,
+    Html,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// A enum to specify the color of the table.
+/// `Rgb` is a true color, `Ansi256` is a color in the 256-color palette of terminals.
 pub enum OutputColor {
     Black,
     Red,
@@ -44,8 +49,14 @@ pub enum OutputColor {
     Yellow,
     Grey,
     White,
+    Rgb(u8, u8, u8),
+    Ansi256(u8),
 }
 
+/// Regex of a color in arguments, a single letter, `#RRGGBB` or `@N` with N from 0 to 255
+const COLOR_PATTERN: &str =
+    r"(?:[rgbyxwRGBYXW]|#[0-9a-fA-F]{6}|@(?:25[0-5]|2[0-4][0-9]|1[0-9]{2}|[1-9]?[0-9]))";
+
 impl FromStr for OutputColor {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -56,6 +67,18 @@ impl FromStr for OutputColor {
             "y" | "Y" => Ok(OutputColor::Yellow),
             "x" | "X" => Ok(OutputColor::Grey),
             "w" | "W" => Ok(OutputColor::White),
+            _ if s.len() == 7 && s.starts_with('#') => {
+                let hex = |range| {
+                    s.get(range)
+                        .and_then(|part| u8::from_str_radix(part, 16).ok())
+                        .ok_or(())
+                };
+                Ok(OutputColor::Rgb(hex(1..3)?, hex(3..5)?, hex(5..7)?))
+            }
+            _ if s.starts_with('@') => s[1..]
+                .parse::<u8>()
+                .map(OutputColor::Ansi256)
+                .map_err(|_| ()),
             _ => Err(()),
         }
     }
@@ -71,6 +94,8 @@ impl std::fmt::Display for OutputColor {
             OutputColor::Yellow => write!(f, "Yellow"),
             OutputColor::Grey => write!(f, "Grey"),
             OutputColor::White => write!(f, "White"),
+            OutputColor::Rgb(r, g, b) => write!(f, "#{:02X}{:02X}{:02X}", r, g, b),
+            OutputColor::Ansi256(n) => write!(f, "@{}", n),
         }
     }
 }
@@ -81,6 +106,94 @@ impl Default for OutputColor {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+/// A enum to specify how floats are shown, `Auto` is the shortest form without exponent,
+/// `Scientific` is like `1.5e-20`, `Precision(n)` has `n` digits after the decimal point.
+pub enum FloatFormat {
+    #[default]
+    Auto,
+    Scientific,
+    Precision(usize),
+}
+
+impl FromStr for FloatFormat {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(FloatFormat::Auto),
+            "sci" | "e" => Ok(FloatFormat::Scientific),
+            _ => s
+                .parse::<usize>()
+                .map(FloatFormat::Precision)
+                .map_err(|_| ()),
+        }
+    }
+}
+
+/// Validate the float format, `auto`, `sci` or the number of digits after the decimal point
+pub(crate) fn validate_float_format(s: &str) -> Result<FloatFormat, Box<ArgError>> {
+    FloatFormat::from_str(s).map_err(|_| {
+        Box::new(ArgError::new(
+            ArgErrorKind::WrongFormat,
+            Some("It should be `auto`, `sci` or the number of digits after the point.".to_string()),
+            Some(s.to_string()),
+            Some(s.to_string()),
+            Some((0, s.len())),
+            None,
+        ))
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+/// A enum to specify whether colors are shown in console, `Auto` shows them only if
+/// the standard output is a terminal
+pub enum ColorChoice {
+    #[default]
+    Always,
+    Never,
+    Auto,
+}
+
+impl ColorChoice {
+    /// Whether the colors should be shown in console
+    pub fn use_color(self) -> bool {
+        use std::io::IsTerminal;
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => std::io::stdout().is_terminal(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+/// A enum to specify the borders in console, `Full` draws a `|` between cells and a rule between
+/// lines, `Minimal` only draws a rule at the top, below the first line and at the bottom
+pub enum BorderStyle {
+    #[default]
+    Full,
+    Minimal,
+}
+
+impl BorderStyle {
+    /// The border between two cells
+    pub(crate) fn cell_border(self) -> &'static str {
+        match self {
+            BorderStyle::Full => "\x1b[90m|\x1b[0m",
+            BorderStyle::Minimal => " ",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+/// A enum to specify the alignment of a block, e.g. the whole table in a target width
+pub enum Alignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
 #[derive(Debug, PartialEq, Parser)]
 pub struct OutputArgs {
     #[command(flatten)]
@@ -110,7 +223,8 @@ pub struct OutputSettings {
 
     #[arg(short = 'S', long, value_parser = validate_export_subtable)]
     /// Use a number or range end with `l/c` to specify the line or column
-    /// Export the subtable of the cross parts of the lines and columns
+    /// Export the subtable of the cross parts of the lines and columns, in the order typed
+    /// and the repeated ones are repeated, e.g. `3c,1c,1c`
     pub export_subtable: Option<(Vec<usize>, Vec<usize>)>,
 }
 
@@ -131,6 +245,8 @@ fn validate_output(s: &str) -> Result<(String, OutputFormat), ArgError> {
         "csv" => OutputFormat::Csv,
         "txt" => OutputFormat::Txt,
         "xls" | "xlsx" => OutputFormat::Exls,
+        "svg" => OutputFormat::Svg,
+        "html" | "htm" => OutputFormat::Html,
         _ => {
             return Err(ArgError::new(
                 ArgErrorKind::FormatError,
@@ -146,7 +262,8 @@ fn validate_output(s: &str) -> Result<(String, OutputFormat), ArgError> {
     Ok((s.to_string(), format))
 }
 
-fn validate_export_subtable(s: &str) -> Result<(Vec<usize>, Vec<usize>), ArgError> {
+/// Validate the subtable, the order and the duplicates of lines and columns are kept as typed
+fn validate_export_subtable(s: &str) -> Result<(Vec<usize>, Vec<usize>), Box<ArgError>> {
     // regex to check if input is valid
     let regex_set = RegexSet::new(&[
         // 0. correct range
@@ -181,14 +298,14 @@ fn validate_export_subtable(s: &str) -> Result<(Vec<usize>, Vec<usize>), ArgErro
     for part in parts {
         let matches = regex_set.matches(part).into_iter().collect::<Vec<_>>();
         if matches.is_empty() {
-            return Err(ArgError::new(
+            return Err(Box::new(ArgError::new(
                 ArgErrorKind::WrongFormat,
                 Some("There is more than one error in this part".to_string()),
                 Some(part.to_string()),
                 Some(s.to_string()),
                 Some((location, location + part.len())),
                 None,
-            ));
+            )));
         } else if matches[0] == 0 {
             let ((start, end), lc) = parse_range_subtable(part);
             match lc {
@@ -220,14 +337,14 @@ fn validate_export_subtable(s: &str) -> Result<(Vec<usize>, Vec<usize>), ArgErro
                 Some(s.to_string()),
                 Some((location, location + part.len())),
             );
-            return Err(ArgError::new(
+            return Err(Box::new(ArgError::new(
                 ArgErrorKind::WrongFormat,
                 Some(range_error.message(ErrorLevel::Warning).to_string()),
                 Some(part.to_string()),
                 Some(s.to_string()),
                 Some((location, location + part.len())),
                 None,
-            ));
+            )));
         } else if matches[0] == 3 {
             let range_error = RangeError::new(
                 RangeErrorKind::RightSideError,
@@ -235,14 +352,14 @@ fn validate_export_subtable(s: &str) -> Result<(Vec<usize>, Vec<usize>), ArgErro
                 Some(s.to_string()),
                 Some((location, location + part.len())),
             );
-            return Err(ArgError::new(
+            return Err(Box::new(ArgError::new(
                 ArgErrorKind::WrongFormat,
                 Some(range_error.message(ErrorLevel::Warning).to_string()),
                 Some(part.to_string()),
                 Some(s.to_string()),
                 Some((location, location + part.len())),
                 None,
-            ));
+            )));
         } else if matches[0] == 4 {
             let range_error = RangeError::new(
                 RangeErrorKind::BothSidesError,
@@ -250,14 +367,14 @@ fn validate_export_subtable(s: &str) -> Result<(Vec<usize>, Vec<usize>), ArgErro
                 Some(s.to_string()),
                 Some((location, location + part.len())),
             );
-            return Err(ArgError::new(
+            return Err(Box::new(ArgError::new(
                 ArgErrorKind::WrongFormat,
                 Some(range_error.message(ErrorLevel::Warning).to_string()),
                 Some(part.to_string()),
                 Some(s.to_string()),
                 Some((location, location + part.len())),
                 None,
-            ));
+            )));
         } else if matches[0] == 5 {
             let range_error = RangeError::new(
                 RangeErrorKind::SingleNumberError,
@@ -265,14 +382,14 @@ fn validate_export_subtable(s: &str) -> Result<(Vec<usize>, Vec<usize>), ArgErro
                 Some(s.to_string()),
                 Some((location, location + part.len())),
             );
-            return Err(ArgError::new(
+            return Err(Box::new(ArgError::new(
                 ArgErrorKind::WrongFormat,
                 Some(range_error.message(ErrorLevel::Warning).to_string()),
                 Some(part.to_string()),
                 Some(s.to_string()),
                 Some((location, location + part.len())),
                 None,
-            ));
+            )));
         } else if matches[0] == 6 || matches[0] == 7 {
             let keyword_missing = KeywordMissing::new(
                 Some(part.to_string()),
@@ -280,21 +397,17 @@ fn validate_export_subtable(s: &str) -> Result<(Vec<usize>, Vec<usize>), ArgErro
                 Some((location, location + part.len())),
                 "line or column".to_string(),
             );
-            return Err(ArgError::new(
+            return Err(Box::new(ArgError::new(
                 ArgErrorKind::WrongFormat,
                 Some(keyword_missing.message(ErrorLevel::Warning).to_string()),
                 Some(part.to_string()),
                 Some(s.to_string()),
                 Some((location, location + part.len())),
                 None,
-            ));
+            )));
         }
         location += part.len();
     }
-    lines.sort();
-    lines.dedup();
-    columns.sort();
-    columns.dedup();
     Ok((lines, columns))
 }
 
@@ -303,25 +416,25 @@ fn validate_export_color(
 ) -> Result<(Vec<(usize, OutputColor)>, Vec<(usize, OutputColor)>), ArgError> {
     let regex_set = RegexSet::new(&[
         // 0. correct range
-        r"^[0-9]+-[0-9]+[rgbyxwRGYBXW][lcLC]$",
+        format!(r"^[0-9]+-[0-9]+{}[lcLC]$", COLOR_PATTERN),
         // 1. correct single
-        r"^[0-9]+[rgbyxwRGYBXW][lcLC]$",
+        format!(r"^[0-9]+{}[lcLC]$", COLOR_PATTERN),
         // 2. wrong format in left side of range
-        r"^.*-[0-9]+[rgbyxwRGYBXW][lcLC]$",
+        format!(r"^.*-[0-9]+{}[lcLC]$", COLOR_PATTERN),
         // 3. wrong format in right side of range
-        r"^[0-9]+-.*[rgbyxwRGYBXW][lcLC]$",
+        format!(r"^[0-9]+-.*{}[lcLC]$", COLOR_PATTERN),
         // 4. wrong format in both sides of range
-        r"^.*-.*[rgbyxwRGYBXW][lcLC]$",
+        format!(r"^.*-.*{}[lcLC]$", COLOR_PATTERN),
         // 5. wrong format in single
-        r"^.*[rgbyxwRGYBXW][lcLC]$",
+        format!(r"^.*{}[lcLC]$", COLOR_PATTERN),
         // 6. wrong format in line/column (range)
-        r"^[0-9]+-[0-9]+[rgbyxwRGBYXW].*$",
+        format!(r"^[0-9]+-[0-9]+{}.*$", COLOR_PATTERN),
         // 7. wrong format in line/column (single)
-        r"^[0-9]+[rgbyxwRGBYXW].*$",
+        format!(r"^[0-9]+{}.*$", COLOR_PATTERN),
         // 8. wrong format in color (range)
-        r"^[0-9]+-[0-9]+.*[lcLC]$",
+        r"^[0-9]+-[0-9]+.*[lcLC]$".to_string(),
         // 9. wrong format in color (single)
-        r"^[0-9]+.*[lcLC]$",
+        r"^[0-9]+.*[lcLC]$".to_string(),
     ])
     .unwrap();
     let mut lines: Vec<(usize, OutputColor)> = Vec::new();
@@ -516,7 +629,11 @@ fn parse_single_subtable(s: &str) -> (usize, LineColumn) {
 
 fn parse_range_color(s: &str) -> ((usize, usize), OutputColor, LineColumn) {
     static RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r"(?<start>[0-9]+)-(?<end>[0-9]+)(?<color>[rgbyxwRGYBXW])(?<lc>[lcLC])").unwrap()
+        Regex::new(&format!(
+            r"(?<start>[0-9]+)-(?<end>[0-9]+)(?<color>{})(?<lc>[lcLC])",
+            COLOR_PATTERN
+        ))
+        .unwrap()
     });
     let caps = RE.captures(s).unwrap();
     let start = caps["start"].parse::<usize>().unwrap();
@@ -527,8 +644,13 @@ fn parse_range_color(s: &str) -> ((usize, usize), OutputColor, LineColumn) {
 }
 
 fn parse_single_color(s: &str) -> (usize, OutputColor, LineColumn) {
-    static RE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"(?<num>[0-9]+)(?<color>[rgbyxwRGYBXW])(?<lc>[lcLC])").unwrap());
+    static RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(&format!(
+            r"(?<num>[0-9]+)(?<color>{})(?<lc>[lcLC])",
+            COLOR_PATTERN
+        ))
+        .unwrap()
+    });
     let caps = RE.captures(s).unwrap();
     let num = caps["num"].parse::<usize>().unwrap();
     let color = OutputColor::from_str(&caps["color"]).unwrap();
@@ -542,6 +664,26 @@ fn parse_single_color(s: &str) -> (usize, OutputColor, LineColumn) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_export_subtable_order() {
+        let args = OutputArgs::try_parse_from(["output", "-S", "3c,1c,1c"]).unwrap();
+        let spec = args.output_settings.export_subtable.unwrap();
+        let table = crate::table::Table::from_string("a b c\nd e f".to_string(), " ", "\n");
+        let subtable = table.apply_subtable_spec(spec).unwrap();
+        assert_eq!(
+            subtable.get_line(0).unwrap().to_string_raw(','),
+            "c, a, a, "
+        );
+        assert_eq!(
+            subtable.get_line(1).unwrap().to_string_raw(','),
+            "f, d, d, "
+        );
+
+        // get_subtable sorts and dedups them
+        let subtable = table.get_subtable((vec![0], vec![2, 0, 0])).unwrap();
+        assert_eq!(subtable.get_line(0).unwrap().to_string_raw(','), "a, c, ");
+    }
+
     #[test]
     fn test_validate_export_subtable() {
         let true_res = (vec![1, 2, 3, 5], vec![2, 3, 4]);
@@ -554,9 +696,11 @@ mod tests {
         let result = validate_export_subtable("1-3L,2-4C,5l").unwrap();
         assert_eq!(result, true_res);
 
-        // duplicate range, different order
+        // duplicate range, different order, kept as typed
         let result = validate_export_subtable("5l,2-4c,1-2l,1-3l,3-4c").unwrap();
-        assert_eq!(result, true_res);
+        assert_eq!(result, (vec![5, 1, 2, 1, 2, 3], vec![2, 3, 4, 3, 4]));
+        let result = validate_export_subtable("3c,1c,1c").unwrap();
+        assert_eq!(result, (vec![], vec![3, 1, 1]));
 
         // invalid range (as a FEATURE)
         let result = validate_export_subtable("1-3l,2-4c,5l,3-2l").unwrap();
@@ -623,6 +767,39 @@ mod tests {
             .contains("There is more than one error in this part"));
     }
 
+    #[test]
+    fn test_output_color_from_str() {
+        assert_eq!(OutputColor::from_str("r"), Ok(OutputColor::Red));
+        assert_eq!(
+            OutputColor::from_str("#FF8000"),
+            Ok(OutputColor::Rgb(255, 128, 0))
+        );
+        assert_eq!(
+            OutputColor::from_str("#ff8000"),
+            Ok(OutputColor::Rgb(255, 128, 0))
+        );
+        assert_eq!(OutputColor::from_str("@196"), Ok(OutputColor::Ansi256(196)));
+        assert!(OutputColor::from_str("#ff80").is_err());
+        assert!(OutputColor::from_str("#gg8000").is_err());
+        assert!(OutputColor::from_str("@256").is_err());
+        assert_eq!(OutputColor::Rgb(255, 128, 0).to_string(), "#FF8000");
+        assert_eq!(OutputColor::Ansi256(196).to_string(), "@196");
+    }
+
+    #[test]
+    fn test_validate_export_color_extended() {
+        let result = validate_export_color("1#ff0000l,2-3@21c").unwrap();
+        assert_eq!(
+            result,
+            (
+                vec![(1, OutputColor::Rgb(255, 0, 0))],
+                vec![(2, OutputColor::Ansi256(21)), (3, OutputColor::Ansi256(21))]
+            )
+        );
+        assert!(validate_export_color("1@256l,2rc").is_err());
+        assert!(validate_export_color("1#ff00l,2rc").is_err());
+    }
+
     #[test]
     fn test_validate_export_color() {
         let true_res = (
@@ -0,0 +1,146 @@
+/* Str2table core crate for sniffing
+ * Copyright (C) 2024 Peng Zijun, Xia Tingxuan
+ *
+ * This file is part of Str2table.
+ * Foobar is free software: you can redistribute it and/or modify it under the terms of
+ * the GNU General Public License as published by the Free Software Foundation, either
+ * version 3 of the License, or (at your option) any later version.
+ * Str2table is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+ * without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License along with Foobar.
+ * If not, see <https://www.gnu.org/licenses/>.
+ */
+//! # Sniff
+//! Guess how an input should be parsed, i.e. its seperation, whether it is quoted and
+//! whether it has a header, so that users can pick the right arguments
+use crate::setting::ParseOptions;
+use crate::tablecellcore::Tablecellcore;
+use crate::tableline::Tableline;
+
+/// The seperations to try, the former one wins if two are equally good
+const CANDIDATES: [&str; 5] = [",", "\t", ";", "|", " "];
+/// Only the first lines are used to guess
+const SNIFF_LINES: usize = 50;
+
+/// The result of sniffing an input
+#[derive(Debug, Clone, PartialEq)]
+pub struct SniffReport {
+    /// The best-guess seperation
+    pub seperation: String,
+    /// Whether there are cells wrapped in double quotes
+    pub quoted: bool,
+    /// Whether the first line looks like a header
+    pub header: bool,
+    /// How sure the guess of seperation is, from 0 to 1
+    pub confidence: f64,
+}
+
+/// Guess the seperation, quoting and header of the input
+///
+/// Each candidate seperation is scored by the share of lines having the most common number of
+/// cells, a candidate splitting lines into only one cell scores 0. The first line is taken as
+/// header if all its cells are strings while each other line has a cell that is not
+pub fn sniff(s: &str) -> SniffReport {
+    let lines: Vec<&str> = s
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+    let quoted = lines.iter().any(|line| line.contains('"'));
+
+    let mut best: Option<(&str, f64, Vec<Tableline>)> = None;
+    for seperation in CANDIDATES {
+        let options = ParseOptions {
+            quoted,
+            // runs of blank are one seperation, other seperations keep empty cells
+            collapse_empty: seperation == " ",
+            ..ParseOptions::default()
+        };
+        let parsed: Vec<Tableline> = lines
+            .iter()
+            .map(|line| Tableline::from_string_with_options(line.to_string(), seperation, &options))
+            .collect();
+        let score = consistency(&parsed);
+        let better = match &best {
+            Some((_, best_score, _)) => score > *best_score,
+            None => true,
+        };
+        if better {
+            best = Some((seperation, score, parsed));
+        }
+    }
+    let (seperation, confidence, parsed) = best.unwrap();
+
+    let is_string = |core: &Tablecellcore| matches!(core, Tablecellcore::String(_));
+    let header = parsed.len() > 1
+        && parsed[0].0.iter().all(|cell| is_string(&cell.core))
+        && parsed[1..]
+            .iter()
+            .all(|line| line.0.iter().any(|cell| !is_string(&cell.core)));
+
+    SniffReport {
+        seperation: seperation.to_string(),
+        quoted,
+        header,
+        confidence,
+    }
+}
+
+/// The share of lines having the most common number of cells, 0 if that number is not
+/// greater than 1
+fn consistency(lines: &[Tableline]) -> f64 {
+    if lines.is_empty() {
+        return 0.0;
+    }
+    let mut counts: Vec<(usize, usize)> = Vec::new();
+    for line in lines {
+        match counts.iter_mut().find(|(len, _count)| *len == line.len()) {
+            Some((_len, count)) => *count += 1,
+            None => counts.push((line.len(), 1)),
+        }
+    }
+    let (len, count) = counts
+        .iter()
+        .max_by_key(|(len, count)| (*count, *len))
+        .unwrap();
+    if *len <= 1 {
+        0.0
+    } else {
+        *count as f64 / lines.len() as f64
+    }
+}
+
+impl std::fmt::Display for SniffReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "seperation: {:?}", self.seperation)?;
+        writeln!(f, "quoted: {}", self.quoted)?;
+        writeln!(f, "header: {}", self.header)?;
+        write!(f, "confidence: {:.2}", self.confidence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_quoted_csv() {
+        let s = "name,city,age\n\"Smith, John\",\"New York\",42\nJane,\"Paris\",37\n";
+        let report = sniff(s);
+        assert_eq!(report.seperation, ",");
+        assert!(report.quoted);
+        assert!(report.header);
+        assert_eq!(report.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_sniff_tsv() {
+        let s = "1\tfoo bar\t2.5\n2\tbaz\t3.5\n3\tqux quux\t4.5\n";
+        let report = sniff(s);
+        assert_eq!(report.seperation, "\t");
+        assert!(!report.quoted);
+        assert!(!report.header);
+        assert_eq!(report.confidence, 1.0);
+    }
+}
@@ -0,0 +1,254 @@
+/* Str2table core crate for table statistics
+ * Copyright (C) 2024 Peng Zijun, Xia Tingxuan
+ *
+ * This file is part of Str2table.
+ * Foobar is free software: you can redistribute it and/or modify it under the terms of
+ * the GNU General Public License as published by the Free Software Foundation, either
+ * version 3 of the License, or (at your option) any later version.
+ * Str2table is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+ * without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ * You should have received a copy of the GNU General Public License along with Foobar.
+ * If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! # Stats
+//! Summarize a table, i.e. its size, the types of cells and the widest cell of each column,
+//! which is printed instead of the table itself, and infer the type of each column
+use crate::table::Table;
+use crate::tablecellcore::Tablecellcore;
+
+/// The summary of a table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStats {
+    /// The number of lines
+    pub rows: usize,
+    /// The length of the longest line
+    pub columns: usize,
+    /// The summary of each column
+    pub column_stats: Vec<ColumnStats>,
+}
+
+/// The summary of a column
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnStats {
+    pub ints: usize,
+    pub floats: usize,
+    /// The number of non-empty strings
+    pub strings: usize,
+    /// The number of empty strings, missing values, and missing cells of lines too short
+    pub empty: usize,
+    /// The number of bools, rationals and percentages
+    pub others: usize,
+    /// The width of the widest cell in console
+    pub widest: usize,
+}
+
+/// The type of a whole column, which all its cells can be converted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Int,
+    Float,
+    Bool,
+    Rational,
+    Percent,
+    String,
+    /// The column has no cell or only empty ones
+    Unknown,
+}
+
+impl ColumnType {
+    /// The type of a single cell, Unknown for an empty string or a missing value
+    fn of(core: &Tablecellcore) -> ColumnType {
+        match core {
+            Tablecellcore::Int(_) => ColumnType::Int,
+            Tablecellcore::Float(_) => ColumnType::Float,
+            Tablecellcore::Bool(_) => ColumnType::Bool,
+            Tablecellcore::Rational(_, _) => ColumnType::Rational,
+            Tablecellcore::Percent(_) => ColumnType::Percent,
+            Tablecellcore::String(v) if v.is_empty() => ColumnType::Unknown,
+            Tablecellcore::Empty => ColumnType::Unknown,
+            Tablecellcore::String(_) => ColumnType::String,
+        }
+    }
+
+    /// The type both types can be converted to, ints are widened to floats or rationals,
+    /// other different types can only be strings
+    fn unify(self, other: ColumnType) -> ColumnType {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (ColumnType::Unknown, t) | (t, ColumnType::Unknown) => t,
+            (ColumnType::Int, ColumnType::Float) | (ColumnType::Float, ColumnType::Int) => {
+                ColumnType::Float
+            }
+            (ColumnType::Int, ColumnType::Rational) | (ColumnType::Rational, ColumnType::Int) => {
+                ColumnType::Rational
+            }
+            _ => ColumnType::String,
+        }
+    }
+}
+
+impl Table {
+    /// Infer the type of each column from its cells, e.g. ints and floats make a float column
+    /// and any string makes a string column. Empty and missing cells are ignored
+    pub fn infer_column_types(&self) -> Vec<ColumnType> {
+        let mut types = vec![ColumnType::Unknown; self.get_longest_row()];
+        for row in 0..self.len() {
+            let line = self.get_line(row).unwrap();
+            for (col, column_type) in types.iter_mut().enumerate() {
+                if let Some(cell) = line.get_cell(col) {
+                    *column_type = column_type.unify(ColumnType::of(&cell.core));
+                }
+            }
+        }
+        types
+    }
+
+    /// Summarize the table, see ```TableStats```
+    pub fn stats(&self) -> TableStats {
+        let columns = self.get_longest_row();
+        let mut column_stats = vec![ColumnStats::default(); columns];
+        for row in 0..self.len() {
+            let line = self.get_line(row).unwrap();
+            for (col, stats) in column_stats.iter_mut().enumerate() {
+                let cell = match line.get_cell(col) {
+                    Some(cell) => cell,
+                    None => {
+                        stats.empty += 1;
+                        continue;
+                    }
+                };
+                match &cell.core {
+                    Tablecellcore::Int(_) => stats.ints += 1,
+                    Tablecellcore::Float(_) => stats.floats += 1,
+                    Tablecellcore::String(v) if v.is_empty() => stats.empty += 1,
+                    Tablecellcore::Empty => stats.empty += 1,
+                    Tablecellcore::String(_) => stats.strings += 1,
+                    _ => stats.others += 1,
+                }
+                stats.widest = stats.widest.max(cell.len());
+            }
+        }
+        TableStats {
+            rows: self.len(),
+            columns,
+            column_stats,
+        }
+    }
+}
+
+impl std::fmt::Display for TableStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "rows: {}", self.rows)?;
+        write!(f, "columns: {}", self.columns)?;
+        // columns are 1-based like the other arguments
+        for (col, stats) in self.column_stats.iter().enumerate() {
+            write!(
+                f,
+                "\ncolumn {}: int {}, float {}, string {}, empty {}, other {}, widest {}",
+                col + 1,
+                stats.ints,
+                stats.floats,
+                stats.strings,
+                stats.empty,
+                stats.others,
+                stats.widest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_column_types() {
+        let types = |s: &str| {
+            let options = crate::setting::ParseOptions {
+                collapse_empty: false,
+                rational: true,
+                ..Default::default()
+            };
+            Table::from_string_with_options(s.to_string(), ",", "\n", &options).infer_column_types()
+        };
+        // homogeneous
+        assert_eq!(
+            types("1,a,true,1.5\n2,b,false,2.5"),
+            [
+                ColumnType::Int,
+                ColumnType::String,
+                ColumnType::Bool,
+                ColumnType::Float
+            ]
+        );
+        // heterogeneous
+        assert_eq!(
+            types("1,1,1,1/2,true\n2.5,x,1/3,3,1"),
+            [
+                ColumnType::Float,
+                ColumnType::String,
+                ColumnType::Rational,
+                ColumnType::Rational,
+                ColumnType::String
+            ]
+        );
+        // empty and missing cells are ignored
+        assert_eq!(
+            types("1,,\n,,\n3"),
+            [ColumnType::Int, ColumnType::Unknown, ColumnType::Unknown]
+        );
+        assert!(Table::new().infer_column_types().is_empty());
+    }
+
+    #[test]
+    fn test_stats() {
+        let s = "name,n,x\nalice,1,2.5\nbob,,true\n世界,3".to_string();
+        let options = crate::setting::ParseOptions {
+            collapse_empty: false,
+            ..Default::default()
+        };
+        let table = Table::from_string_with_options(s, ",", "\n", &options);
+        let stats = table.stats();
+        assert_eq!(stats.rows, 4);
+        assert_eq!(stats.columns, 3);
+        assert_eq!(
+            stats.column_stats[0],
+            ColumnStats {
+                strings: 4,
+                widest: 5,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            stats.column_stats[1],
+            ColumnStats {
+                ints: 2,
+                strings: 1,
+                empty: 1,
+                widest: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            stats.column_stats[2],
+            ColumnStats {
+                ints: 0,
+                floats: 1,
+                strings: 1,
+                empty: 1,
+                others: 1,
+                widest: 4,
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "rows: 4\ncolumns: 3\n\
+             column 1: int 0, float 0, string 4, empty 0, other 0, widest 5\n\
+             column 2: int 2, float 0, string 1, empty 1, other 0, widest 1\n\
+             column 3: int 0, float 1, string 1, empty 1, other 1, widest 4"
+        );
+    }
+}
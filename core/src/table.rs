@@ -14,205 +14,827 @@
 
 //! # Table
 //! Include a vector of tablelines, representing a table.
+use crate::error::arg_error::{ArgError, ArgErrorKind};
+use crate::error::duplicate_keys::DuplicateKeys;
+use crate::error::ragged_row::RaggedRow;
 use crate::export::Export;
 use crate::setting;
+use crate::setting::output::{Alignment, BorderStyle, ColorChoice};
+use crate::setting::FloatFormat;
 use crate::setting::InputArgs;
 use crate::setting::OutputColor;
+use crate::setting::ParseOptions;
 use crate::tablecell::Tablecell;
 use crate::tablecellcore::Tablecellcore;
-use crate::tableline::Tableline;
+use crate::tableline::{truncate_with_options, Tableline};
+use ibig::IBig;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use unicode_width::UnicodeWidthStr;
 use xlsxwriter::prelude::*;
 
-pub struct Table(Vec<Tableline>);
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Table {
+    lines: Vec<Tableline>,
+    /// Horizontal spans of merged cells, `(row, col_start, col_end)` with both ends included
+    spans: Vec<(usize, usize, usize)>,
+    /// How floats are shown in console, which is not part of the data
+    #[cfg_attr(feature = "serde", serde(skip))]
+    float_format: FloatFormat,
+    /// Cells wider than this are truncated with `…` in console
+    #[cfg_attr(feature = "serde", serde(skip))]
+    max_column_width: Option<usize>,
+    /// Whether cells wider than `max_column_width` are wrapped instead of truncated
+    #[cfg_attr(feature = "serde", serde(skip))]
+    wrap_cells: bool,
+    /// Whether colors are shown in console
+    #[cfg_attr(feature = "serde", serde(skip))]
+    color_choice: ColorChoice,
+    /// The number of spaces on both sides of each cell in console
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_cell_padding"))]
+    cell_padding: usize,
+    /// The borders in console
+    #[cfg_attr(feature = "serde", serde(skip))]
+    border_style: BorderStyle,
+    /// Shown for the missing cells at the end of short lines in console, txt and csv
+    #[cfg_attr(feature = "serde", serde(skip))]
+    empty_placeholder: Option<String>,
+    /// The alignment of the whole table in `target_width` in console
+    #[cfg_attr(feature = "serde", serde(skip))]
+    block_alignment: Alignment,
+    /// The width the table is aligned in, e.g. the width of the terminal
+    #[cfg_attr(feature = "serde", serde(skip))]
+    target_width: Option<usize>,
+    /// The labels of colors shown in the color legend
+    #[cfg_attr(feature = "serde", serde(skip))]
+    color_labels: Vec<(OutputColor, String)>,
+    /// Show the type of each cell after its value in console, e.g. `3<int>`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    show_types: bool,
+}
+
+/// One space on both sides of each cell, also used when a table is deserialized
+fn default_cell_padding() -> usize {
+    1
+}
 
 impl Table {
     pub fn new() -> Table {
-        Table(Vec::new())
+        Table::from_vec(Vec::new())
     }
 
     pub fn from_vec(lines: Vec<Tableline>) -> Table {
-        Table(lines)
+        Table {
+            lines,
+            spans: Vec::new(),
+            float_format: FloatFormat::default(),
+            max_column_width: None,
+            wrap_cells: false,
+            color_choice: ColorChoice::default(),
+            cell_padding: default_cell_padding(),
+            border_style: BorderStyle::default(),
+            empty_placeholder: None,
+            block_alignment: Alignment::default(),
+            target_width: None,
+            color_labels: Vec::new(),
+            show_types: false,
+        }
     }
 
-    ///Parse a string to a table with the given force parse rule
+    /// Parse a string to a table with the force parse rule of `args`, the lines or columns in
+    /// the rule are 1-based. Comment lines and empty lines are skipped and not counted
+    ///
+    /// The cells that couldn't be forced to the type use ```auto_from()```, use
+    /// ```from_string_with_force_parse_with_warnings()``` to know which ones
     pub fn from_string_with_force_parse(
         s: String,
         seperation: &str,
         end_line: &str,
         args: &InputArgs,
     ) -> Table {
-        let mut s = s;
-        if !end_line.contains("\n") {
-            // remove '\n' from input
-            s = s.replace("\n", "");
-        }
+        Table::from_string_with_force_parse_with_warnings(s, seperation, end_line, args).0
+    }
+
+    /// Parse a string like ```from_string_with_force_parse()```, and return a warning with the
+    /// table for each line or column having cells that couldn't be forced to the type. Empty
+    /// cells have nothing to force and never cause a warning. The warnings of cells truncated
+    /// to `cell_max_bytes` come first
+    pub fn from_string_with_force_parse_with_warnings(
+        s: String,
+        seperation: &str,
+        end_line: &str,
+        args: &InputArgs,
+    ) -> (Table, Vec<ArgError>) {
+        let s = remove_line_breaks(s, end_line);
+        let mut failures: Vec<ForceParseFailure> = Vec::new();
+        let mut warnings: Vec<ArgError> = Vec::new();
 
         let mut lines: Vec<Tableline> = match args.force_parse.as_ref().unwrap().1 {
             setting::LineColumn::Line => s
                 .split(end_line)
+                .filter(|line| !args.parse_options.is_comment(line) && !line.trim().is_empty())
                 .enumerate()
                 .map(|(line_num, line)| {
-                    let exists = args
+                    let force_type = args
                         .force_parse
                         .as_ref()
                         .unwrap()
                         .0
                         .iter()
-                        .find(|(a, _tmp)| *a == line_num);
-                    if exists.is_some() {
-                        Tableline::from_string_with_force_parse_line(
-                            line.to_string(),
-                            seperation,
-                            exists.unwrap().1,
-                        )
-                    } else {
-                        Tableline::from_string(line.to_string(), seperation)
+                        .find(|(a, _tmp)| *a == line_num + 1)
+                        .map(|(_, force_type)| *force_type);
+                    let line = Tableline::from_string_with_force_parse_line_and_warnings(
+                        line.to_string(),
+                        seperation,
+                        force_type,
+                        args,
+                        &mut warnings,
+                    );
+                    if let Some(force_type) = force_type {
+                        for cell in line.0.iter() {
+                            record_force_failure(&mut failures, line_num + 1, force_type, cell);
+                        }
                     }
+                    line
                 })
                 .collect(),
             setting::LineColumn::Column => s
                 .split(end_line)
-                .enumerate()
-                .map(|(_id, line)| {
-                    Tableline::from_string_with_force_parse_column(
+                .filter(|line| !args.parse_options.is_comment(line))
+                .map(|line| {
+                    let line = Tableline::from_string_with_force_parse_column_and_warnings(
                         line.to_string(),
                         seperation,
                         args,
-                    )
+                        &mut warnings,
+                    );
+                    for (column, force_type) in args.force_parse.as_ref().unwrap().0.iter() {
+                        if let Some(cell) = line.get_cell(column - 1) {
+                            record_force_failure(&mut failures, *column, *force_type, cell);
+                        }
+                    }
+                    line
                 })
                 .collect(),
         };
-        lines.retain(|line| line.len() > 0);
-        Table(lines)
+        lines.retain(|line| !line.is_empty());
+
+        let line_column = args.force_parse.as_ref().unwrap().1;
+        failures.sort_by_key(|failure| failure.number);
+        warnings.extend(
+            failures
+                .into_iter()
+                .map(|failure| failure.to_warning(line_column)),
+        );
+        (Table::from_vec(lines), warnings)
     }
 
     /// Parse a string to a table, assuming the string has '\n' as line seperator
     pub fn from_string(s: String, seperation: &str, end_line: &str) -> Table {
-        let mut s = s;
-        if !end_line.contains("\n") {
-            // remove '\n' from input
-            s = s.replace("\n", "");
+        Table::from_string_with_options(s, seperation, end_line, &ParseOptions::default())
+    }
+
+    /// Parse a string to a table like ```from_string()``` with the given parse options
+    pub fn from_string_with_options(
+        s: String,
+        seperation: &str,
+        end_line: &str,
+        options: &ParseOptions,
+    ) -> Table {
+        Table::from_lines(s, end_line, options, |line| {
+            Tableline::from_string_with_options(line.to_string(), seperation, options)
+        })
+    }
+
+    /// Parse a string to a table like ```from_string_with_options()```, but `seperation` is a regex,
+    /// e.g. `\s+` for one or more blanks. The regex is compiled once for all the lines
+    ///
+    /// Return ArgError if the regex is invalid
+    pub fn from_string_with_regex(
+        s: String,
+        seperation: &str,
+        end_line: &str,
+        options: &ParseOptions,
+    ) -> Result<Table, Box<ArgError>> {
+        let regex = seperation_regex(seperation)?;
+        Ok(Table::from_lines(s, end_line, options, |line| {
+            Tableline::from_string_with_regex(line.to_string(), &regex, options)
+        }))
+    }
+
+    /// Read all the text from a reader, e.g. a file, a pipe or a buffer in memory, and parse it
+    /// to a table with the parse mode, parse options and force parse rule of `args`
+    ///
+    /// Return Err if the reader fails or the text is not utf-8. If the seperation is an invalid
+    /// regex, or `regex_seperation` is used with a force parse rule, the error is an
+    /// `InvalidInput` one with the ArgError inside
+    ///
+    /// The warnings of force parse are dropped, use ```from_reader_with_warnings()``` to get them
+    pub fn from_reader<R: std::io::BufRead>(
+        r: R,
+        seperation: &str,
+        end_line: &str,
+        args: &InputArgs,
+    ) -> std::io::Result<Table> {
+        Table::from_reader_with_warnings(r, seperation, end_line, args).map(|(table, _)| table)
+    }
+
+    /// Read a text like ```from_reader()```, and return the warnings with the table, which are
+    /// those of cells truncated to `cell_max_bytes` and of force parse, see
+    /// ```from_string_with_force_parse_with_warnings()```
+    pub fn from_reader_with_warnings<R: std::io::BufRead>(
+        mut r: R,
+        seperation: &str,
+        end_line: &str,
+        args: &InputArgs,
+    ) -> std::io::Result<(Table, Vec<ArgError>)> {
+        let options = &args.parse_options;
+        if args.parse_mode == setting::ParseMode::A
+            && options.regex_seperation
+            && args.force_parse.is_some()
+        {
+            let error = ArgError::new(
+                ArgErrorKind::Conflicts,
+                Some("the force parse rule can't be used with a regex seperation.".to_string()),
+                Some("--force-parse".to_string()),
+                Some("--regex-seperation --force-parse".to_string()),
+                None,
+                Some("Please remove one of them.".to_string()),
+            );
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, error));
         }
+        let mut s = String::new();
+        r.read_to_string(&mut s)?;
+        let mut warnings = Vec::new();
+        let table = match args.parse_mode {
+            setting::ParseMode::A if options.regex_seperation => {
+                let regex = seperation_regex(seperation)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, *e))?;
+                Table::from_lines(s, end_line, options, |line| {
+                    Tableline::from_string_with_regex_and_warnings(
+                        line.to_string(),
+                        &regex,
+                        options,
+                        &mut warnings,
+                    )
+                })
+            }
+            setting::ParseMode::A if args.force_parse.is_some() => {
+                return Ok(Table::from_string_with_force_parse_with_warnings(
+                    s, seperation, end_line, args,
+                ));
+            }
+            setting::ParseMode::A => Table::from_lines(s, end_line, options, |line| {
+                Tableline::from_string_with_options_and_warnings(
+                    line.to_string(),
+                    seperation,
+                    options,
+                    &mut warnings,
+                )
+            }),
+            setting::ParseMode::S => {
+                let s = remove_line_breaks(s, end_line);
+                s.split(end_line)
+                    .map(|line| {
+                        Tableline::from_string_force_and_warnings(
+                            line.to_string(),
+                            seperation,
+                            options,
+                            &mut warnings,
+                        )
+                    })
+                    .collect()
+            }
+            setting::ParseMode::N => Table::from_lines(s, end_line, options, |line| {
+                Tableline::from_string_force_with_empty_and_warnings(
+                    line.to_string(),
+                    seperation,
+                    options,
+                    &mut warnings,
+                )
+            }),
+        };
+        Ok((table, warnings))
+    }
+
+    /// Split the string to lines, skip comment lines and parse each line with `parse_line`,
+    /// empty lines are removed
+    fn from_lines<F: FnMut(&str) -> Tableline>(
+        s: String,
+        end_line: &str,
+        options: &ParseOptions,
+        parse_line: F,
+    ) -> Table {
+        let s = remove_line_breaks(s, end_line);
         let mut lines: Vec<Tableline> = s
             .split(end_line)
-            .map(|line| Tableline::from_string(line.to_string(), seperation))
+            .filter(|line| !options.is_comment(line))
+            .map(parse_line)
+            .collect();
+        lines.retain(|line| !line.is_empty());
+        Table::from_vec(lines)
+    }
+
+    /// Parse a RFC 4180 csv string to a table, a quoted field can contain `,`, line breaks
+    /// and `""` as an escaped quote. Fields are not trimmed and empty fields are kept
+    ///
+    /// Each field is parsed with the parse options and force parse rule of `args`,
+    /// the seperation and end of line of `args` are not used
+    ///
+    /// Return Err with the first record the csv reader fails to read
+    pub fn from_csv(s: &str, args: &InputArgs) -> Result<Table, csv::Error> {
+        Table::from_csv_with_warnings(s, args).map(|(table, _)| table)
+    }
+
+    /// Parse a csv string like ```from_csv()```, and return the warnings of cells truncated to
+    /// `cell_max_bytes` with the table
+    pub fn from_csv_with_warnings(
+        s: &str,
+        args: &InputArgs,
+    ) -> Result<(Table, Vec<ArgError>), csv::Error> {
+        let options = &args.parse_options;
+        let mut warnings = Vec::new();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(s.as_bytes());
+        let lines = reader
+            .records()
+            .enumerate()
+            .map(|(line_num, record)| {
+                let cells = record?
+                    .iter()
+                    .enumerate()
+                    .map(|(column_num, field)| {
+                        let field = truncate_with_options(field, options, &mut warnings);
+                        let force_type = args.force_parse.as_ref().and_then(|(rule, kind)| {
+                            let index = match kind {
+                                setting::LineColumn::Line => line_num,
+                                setting::LineColumn::Column => column_num,
+                            };
+                            rule.iter().find(|(a, _)| *a == index + 1).map(|(_, t)| *t)
+                        });
+                        match force_type {
+                            Some(force_type) => Tablecell::from_type(field.to_string(), force_type),
+                            None => Tablecell::auto_from_with_options(field.to_string(), options),
+                        }
+                    })
+                    .collect();
+                Ok(Tableline::from_vec(cells))
+            })
+            .collect::<Result<Vec<Tableline>, csv::Error>>()?;
+        Ok((Table::from_vec(lines), warnings))
+    }
+
+    /// Parse a tab seperated string to a table, lines end with `\n` or `\r\n`.
+    /// Fields are never quoted or trimmed, empty fields are kept and empty lines are skipped
+    pub fn from_tsv(s: String) -> Table {
+        let options = ParseOptions::default();
+        let lines: Vec<Tableline> = s
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                Tableline::from_vec(
+                    line.split('\t')
+                        .map(|field| Tablecell::auto_from_with_options(field.to_string(), &options))
+                        .collect(),
+                )
+            })
             .collect();
-        lines.retain(|line| line.len() > 0);
-        Table(lines)
+        Table::from_vec(lines)
     }
 
     /// Parse a string to a table, force the cell as string, assuming the string has '\n' as line seperator
     pub fn from_string_force(s: String, seperation: &str, end_line: &str) -> Table {
-        let mut s = s;
-        if !end_line.contains("\n") {
-            // remove '\n' from input
-            s = s.replace("\n", "");
-        }
+        let s = remove_line_breaks(s, end_line);
         let lines: Vec<Tableline> = s
             .split(end_line)
             .map(|line| Tableline::from_string_force(line.to_string(), seperation))
             .collect();
-        Table(lines)
+        Table::from_vec(lines)
+    }
+
+    /// Parse a string to a table like ```from_string_with_options()```, but force the cells as
+    /// string, and the empty cells are kept as missing values, e.g. the middle one of `a,,b`
+    pub fn from_string_force_with_empty(
+        s: String,
+        seperation: &str,
+        end_line: &str,
+        options: &ParseOptions,
+    ) -> Table {
+        Table::from_lines(s, end_line, options, |line| {
+            Tableline::from_string_force_with_empty(line.to_string(), seperation, options)
+        })
     }
 
     /// Push one line to the end of table
     pub fn push_line(&mut self, line: Tableline) {
-        self.0.push(line);
+        self.lines.push(line);
     }
 
-    /// Pop one line from the end of table
+    /// Pop one line from the end of table, the merged cells of it are dropped
     pub fn pop_line(&mut self) -> Option<Tableline> {
-        self.0.pop()
+        let line = self.lines.pop();
+        let len = self.lines.len();
+        self.remap_span_rows(|row| (row < len).then_some(row));
+        line
     }
 
     /// Insert a line at the index, return Err if the index is out of range
     pub fn insert_line(&mut self, index: usize, line: Tableline) -> Result<(), String> {
-        if index > self.0.len() {
+        self.insert_lines(index, vec![line])
+    }
+
+    /// Remove a line at the index, return Err if the index is out of range.
+    /// The merged cells of it are dropped
+    pub fn remove_line(&mut self, index: usize) -> Result<Tableline, String> {
+        if index >= self.lines.len() {
             return Err("Index out of range".to_string());
         }
-        self.0.insert(index, line);
-        Ok(())
+        self.remap_span_rows(|row| match row.cmp(&index) {
+            std::cmp::Ordering::Less => Some(row),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(row - 1),
+        });
+        Ok(self.lines.remove(index))
     }
 
-    /// Remove a line at the index, return Err if the index is out of range
-    pub fn remove_line(&mut self, index: usize) -> Result<Tableline, String> {
-        if index >= self.0.len() {
+    /// Swap two lines, return Err if either index is out of range.
+    /// The merged cells move with their lines
+    pub fn swap_rows(&mut self, a: usize, b: usize) -> Result<(), String> {
+        if a >= self.lines.len() || b >= self.lines.len() {
             return Err("Index out of range".to_string());
         }
-        Ok(self.0.remove(index))
+        self.remap_span_rows(|row| {
+            Some(if row == a {
+                b
+            } else if row == b {
+                a
+            } else {
+                row
+            })
+        });
+        self.lines.swap(a, b);
+        Ok(())
     }
 
     /// Push multiple lines to the end of table
     pub fn push_lines(&mut self, lines: Vec<Tableline>) {
-        self.0.extend(lines);
+        self.lines.extend(lines);
     }
 
-    /// Pop multiple lines from the end of table
+    /// Pop multiple lines from the end of table, the merged cells of them are dropped
     pub fn pop_lines(&mut self, n: usize) -> Vec<Tableline> {
-        let n = n.min(self.0.len());
+        let n = n.min(self.lines.len());
         let mut lines = Vec::with_capacity(n);
         for _ in 0..n {
-            lines.push(self.0.pop().unwrap());
+            lines.push(self.lines.pop().unwrap());
         }
+        let len = self.lines.len();
+        self.remap_span_rows(|row| (row < len).then_some(row));
         lines
     }
 
-    /// Remove range of lines from the table, return Err if the range is out of range
+    /// Remove range of lines from the table, return Err if the range is out of range.
+    /// The merged cells of them are dropped
     pub fn remove_lines(&mut self, start: usize, end: usize) -> Result<(), String> {
-        if start >= self.0.len() || end >= self.0.len() {
+        if start >= self.lines.len() || end >= self.lines.len() {
             return Err("Index out of range".to_string());
         }
-        self.0.drain(start..=end);
+        let removed = (end + 1).saturating_sub(start);
+        self.remap_span_rows(|row| {
+            if row < start {
+                Some(row)
+            } else if row > end {
+                Some(row - removed)
+            } else {
+                None
+            }
+        });
+        self.lines.drain(start..=end);
         Ok(())
     }
 
     /// Insert multiple lines at the index, return Err if the index is out of range
     pub fn insert_lines(&mut self, index: usize, lines: Vec<Tableline>) -> Result<(), String> {
-        if index > self.0.len() {
+        if index > self.lines.len() {
             return Err("Index out of range".to_string());
         }
-        self.0.splice(index..index, lines);
+        let inserted = lines.len();
+        self.remap_span_rows(|row| Some(if row < index { row } else { row + inserted }));
+        self.lines.splice(index..index, lines);
         Ok(())
     }
 
+    /// Move all the lines of `other` to the end of the table, the spans of `other` are kept
+    /// on the moved lines
+    pub fn append(&mut self, other: Table) {
+        let offset = self.lines.len();
+        self.spans.extend(
+            other
+                .spans
+                .into_iter()
+                .map(|(row, col_start, col_end)| (row + offset, col_start, col_end)),
+        );
+        self.lines.extend(other.lines);
+    }
+
+    /// Append the cells of each line of `other` to the same line of the table
+    ///
+    /// Lines are padded with empty string cells to the width of the table first, so the cells
+    /// of `other` stay in the same columns. Missing lines of either table are filled with
+    /// empty string cells. The spans of `other` are moved with its cells
+    pub fn concat_right(&mut self, other: &Table) {
+        let width = self.get_longest_row();
+        let other_width = other.get_longest_row();
+        let empty = || Tablecell::force_as_string(String::new());
+        while self.lines.len() < other.lines.len() {
+            self.lines.push(Tableline::new());
+        }
+        for (line_num, line) in self.lines.iter_mut().enumerate() {
+            while line.len() < width {
+                line.push_cell(empty());
+            }
+            match other.lines.get(line_num) {
+                Some(other_line) => line.push_cells(other_line.0.clone()),
+                None => {
+                    for _ in 0..other_width {
+                        line.push_cell(empty());
+                    }
+                }
+            }
+        }
+        self.spans.extend(
+            other
+                .spans
+                .iter()
+                .map(|(row, col_start, col_end)| (*row, col_start + width, col_end + width)),
+        );
+    }
+
+    /// Keep only the lines that satisfy the predicate, the order of the remaining lines is preserved.
+    /// The merged cells of the removed lines are dropped
+    pub fn retain_rows<F: Fn(&Tableline) -> bool>(&mut self, f: F) {
+        // the new row of each line, None if it is removed
+        let mut kept = 0;
+        let rows: Vec<Option<usize>> = self
+            .lines
+            .iter()
+            .map(|line| {
+                f(line).then(|| {
+                    kept += 1;
+                    kept - 1
+                })
+            })
+            .collect();
+        self.remap_span_rows(|row| rows.get(row).copied().flatten());
+        let mut rows = rows.into_iter();
+        self.lines.retain(|_| rows.next().flatten().is_some());
+    }
+
     /// Get the number of lines of the table
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.lines.len()
+    }
+
+    /// Check if the table has no lines
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Get the number of lines and the length of the longest row of the table
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.len(), self.get_longest_row())
     }
 
     /// Get the line at the index
     pub fn get_line(&self, index: usize) -> Option<&Tableline> {
-        self.0.get(index)
+        self.lines.get(index)
     }
 
     /// Get the mutable line at the index
     pub fn get_line_mut(&mut self, index: usize) -> Option<&mut Tableline> {
-        self.0.get_mut(index)
+        self.lines.get_mut(index)
+    }
+
+    /// Get the last line, None if the table is empty
+    pub fn last_line(&self) -> Option<&Tableline> {
+        self.lines.last()
+    }
+
+    /// Get the mutable last line, None if the table is empty
+    pub fn last_line_mut(&mut self) -> Option<&mut Tableline> {
+        self.lines.last_mut()
+    }
+
+    /// Get the `n`th line from the end, `0` is the last line. None if there are not enough lines
+    pub fn line_from_end(&self, n: usize) -> Option<&Tableline> {
+        self.lines
+            .len()
+            .checked_sub(n + 1)
+            .map(|index| &self.lines[index])
     }
 
     /// Get the lines of the table at the index range
     pub fn get_lines(&self, start: usize, end: usize) -> Option<&[Tableline]> {
-        if start >= self.0.len() || end >= self.0.len() {
+        if start >= self.lines.len() || end >= self.lines.len() {
             return None;
         }
-        Some(&self.0[start..=end])
+        Some(&self.lines[start..=end])
+    }
+
+    /// Get the lines of the table at the index range like ```get_lines()```, but `end` is
+    /// clamped to the last line. Empty if `start` is past the end or after `end`
+    pub fn get_lines_clamped(&self, start: usize, end: usize) -> &[Tableline] {
+        let end = end.min(self.lines.len().saturating_sub(1));
+        if start >= self.lines.len() || start > end {
+            return &[];
+        }
+        &self.lines[start..=end]
+    }
+
+    /// Get the lines of the table from `start` to the last line, empty if `start` is past the end
+    pub fn get_lines_from(&self, start: usize) -> &[Tableline] {
+        self.lines.get(start..).unwrap_or(&[])
     }
 
     /// Get the mutable lines of the table at the index range
     pub fn get_lines_mut(&mut self, start: usize, end: usize) -> Option<&mut [Tableline]> {
-        if start >= self.0.len() || end >= self.0.len() {
+        if start >= self.lines.len() || end >= self.lines.len() {
             return None;
         }
-        Some(&mut self.0[start..=end])
+        Some(&mut self.lines[start..=end])
     }
 
     /// Get the cell at the index
     pub fn get_cell(&self, (row, col): (usize, usize)) -> Option<&Tablecell> {
-        self.0.get(row).and_then(|line| line.get_cell(col))
+        self.lines.get(row).and_then(|line| line.get_cell(col))
+    }
+
+    /// Replace a cell with `value`, including its color, return Err if the index is out of range
+    pub fn set_cell(&mut self, (row, col): (usize, usize), value: Tablecell) -> Result<(), String> {
+        match self
+            .lines
+            .get_mut(row)
+            .and_then(|line| line.get_cell_mut(col))
+        {
+            Some(cell) => {
+                *cell = value;
+                Ok(())
+            }
+            None => Err("Index out of range".to_string()),
+        }
+    }
+
+    /// Replace the value of a cell and keep its color, return Err if the index is out of range
+    pub fn set_cell_value(
+        &mut self,
+        (row, col): (usize, usize),
+        value: Tablecellcore,
+    ) -> Result<(), String> {
+        match self
+            .lines
+            .get_mut(row)
+            .and_then(|line| line.get_cell_mut(col))
+        {
+            Some(cell) => {
+                cell.set_core(value);
+                Ok(())
+            }
+            None => Err("Index out of range".to_string()),
+        }
+    }
+
+    /// Remove a column from every line, return the removed cells, None if the line is too short
+    ///
+    /// The merged cells covering the column are dropped, those after it are moved left
+    pub fn remove_column(&mut self, col: usize) -> Vec<Option<Tablecell>> {
+        self.spans
+            .retain(|(_row, start, end)| !(*start <= col && col <= *end));
+        for (_row, start, end) in self.spans.iter_mut() {
+            if *start > col {
+                *start -= 1;
+                *end -= 1;
+            }
+        }
+        self.lines
+            .iter_mut()
+            .map(|line| line.remove_cell(col).ok())
+            .collect()
+    }
+
+    /// Insert a column before the index `col`, one cell for each line,
+    /// return Err if the number of cells is not the number of lines or a line is too short
+    ///
+    /// The merged cells the column is inserted into are dropped, those after it are moved right
+    pub fn insert_column(&mut self, col: usize, cells: Vec<Tablecell>) -> Result<(), String> {
+        if cells.len() != self.lines.len() {
+            return Err("Number of cells mismatch".to_string());
+        }
+        if self.lines.iter().any(|line| col > line.len()) {
+            return Err("Index out of range".to_string());
+        }
+        self.spans
+            .retain(|(_row, start, end)| !(*start < col && col <= *end));
+        for (_row, start, end) in self.spans.iter_mut() {
+            if *start >= col {
+                *start += 1;
+                *end += 1;
+            }
+        }
+        for (line, cell) in self.lines.iter_mut().zip(cells) {
+            line.insert_cell(col, cell).unwrap();
+        }
+        Ok(())
+    }
+
+    /// Replace every cell of a column with the result of `f` on it,
+    /// lines too short are left untouched
+    pub fn map_column<F: Fn(&Tablecell) -> Tablecell>(&mut self, col: usize, f: F) {
+        for line in self.lines.iter_mut() {
+            if let Some(cell) = line.get_cell_mut(col) {
+                *cell = f(cell);
+            }
+        }
+    }
+
+    /// Get the cells of a column, one for each line, None if the line is too short
+    pub fn get_column(&self, col: usize) -> Vec<Option<&Tablecell>> {
+        self.lines.iter().map(|line| line.get_cell(col)).collect()
+    }
+
+    /// Rearrange the cells of each line so the `i`th cell is the `order[i]`th cell before,
+    /// columns can be dropped or repeated. A line too short for an index gets an empty cell.
+    ///
+    /// The merged cells are dropped as their columns are moved
+    pub fn reorder_columns(&mut self, order: &[usize]) {
+        for line in self.lines.iter_mut() {
+            let mut new_line = Tableline::new();
+            for col in order {
+                match line.get_cell(*col) {
+                    Some(cell) => new_line.push_cell(cell.clone()),
+                    None => new_line.push_cell(Tablecell::force_as_string(String::new())),
+                }
+            }
+            *line = new_line;
+        }
+        self.spans.clear();
+    }
+
+    /// Swap two columns in every line that has both of them, shorter lines are left untouched
+    ///
+    /// The merged cells are dropped as their columns are moved
+    pub fn swap_columns(&mut self, a: usize, b: usize) {
+        for line in self.lines.iter_mut() {
+            if a < line.len() && b < line.len() {
+                line.0.swap(a, b);
+            }
+        }
+        if a != b {
+            self.spans.clear();
+        }
+    }
+
+    /// Get a new table of the lines whose cell at `col` equals `value`, in the same order and
+    /// with their colors. Cells are equal if they have the same type and value, e.g. `5<int>`
+    /// is not `5<float>`. Lines too short to have the column are dropped
+    pub fn filter_by_column(&self, col: usize, value: &Tablecellcore) -> Table {
+        self.lines
+            .iter()
+            .filter(|line| line.get_cell(col).is_some_and(|cell| cell.core == *value))
+            .cloned()
+            .collect()
     }
 
     /// Get subtable from the table
-    pub fn get_subtable(self, (lines, columns): (Vec<usize>, Vec<usize>)) -> Option<Table> {
+    ///
+    /// The lines and columns are sorted and deduplicated first, so the subtable keeps the
+    /// order of the table and has no repeated line or column. Use `get_subtable_ordered` to
+    /// reorder or repeat them
+    ///
+    /// The cells keep their colors, so the colors set before by the indices of the table
+    /// survive, and colors set after use the indices of the subtable
+    pub fn get_subtable(
+        &self,
+        (mut lines, mut columns): (Vec<usize>, Vec<usize>),
+    ) -> Option<Table> {
+        lines.sort();
+        lines.dedup();
+        columns.sort();
+        columns.dedup();
+        self.get_subtable_ordered((lines, columns))
+    }
+
+    /// Get subtable from the table with the lines and columns in the given order, the
+    /// repeated ones are repeated in the subtable
+    pub fn get_subtable_ordered(
+        &self,
+        (lines, columns): (Vec<usize>, Vec<usize>),
+    ) -> Option<Table> {
         let mut table = Table::new();
         for line in lines {
             if let Some(line) = self.get_line(line) {
@@ -228,208 +850,3158 @@ impl Table {
         Some(table)
     }
 
-    /// Get the length of longest row of the table
-    pub fn get_longest_row(&self) -> usize {
-        self.0.iter().map(|line| line.len()).max().unwrap_or(0)
+    /// Get the subtable with the lines and columns typed by user in `-S`, the numbers are
+    /// 1-based and 0 is ignored
+    ///
+    /// The lines and columns are kept in the given order and can be repeated.
+    /// All the lines (columns) are kept if no line (column) is given
+    pub fn apply_subtable_spec(&self, (lines, columns): (Vec<usize>, Vec<usize>)) -> Option<Table> {
+        let to_index = |nums: Vec<usize>, all: usize| -> Vec<usize> {
+            if nums.is_empty() {
+                (0..all).collect()
+            } else {
+                nums.into_iter()
+                    .filter(|num| *num > 0)
+                    .map(|num| num - 1)
+                    .collect()
+            }
+        };
+        let lines = to_index(lines, self.lines.len());
+        let columns = to_index(columns, self.get_longest_row());
+        self.get_subtable_ordered((lines, columns))
     }
 
-    /// Set the color of a line
-    pub fn set_color_line(&mut self, index: usize, color: OutputColor) {
-        if index >= self.0.len() {
-            return;
-        }
-        for i in 0..self.0[index].len() {
-            self.0[index].get_cell_mut(i).unwrap().set_color(color);
-        }
+    /// Insert a column of 1-based line numbers at the front of the table, so it is
+    /// exported like other data. If `header` is true, the first line is treated as
+    /// header, gets `#` as label and is not counted. The merged cells are moved right
+    pub fn prepend_row_numbers(&mut self, header: bool) {
+        let cells = (0..self.lines.len())
+            .map(|line_num| {
+                if header && line_num == 0 {
+                    Tablecell::force_as_string("#".to_string())
+                } else if header {
+                    Tablecell::auto_from(line_num.to_string())
+                } else {
+                    Tablecell::auto_from((line_num + 1).to_string())
+                }
+            })
+            .collect();
+        self.insert_column(0, cells).unwrap();
     }
 
-    /// Set the color of a column
-    pub fn set_color_column(&mut self, index: usize, color: OutputColor) {
-        for i in 0..self.0.len() {
-            if let Some(cell) = self.0[i].get_cell_mut(index) {
-                cell.set_color(color);
-            }
-        }
+    /// Set how floats are shown in console, the values are not changed
+    pub fn set_float_format(&mut self, format: FloatFormat) {
+        self.float_format = format;
     }
-}
 
-/* --------------------------------- Export --------------------------------- */
+    /// Set the max width of columns in console, wider cells are truncated with a trailing `…`.
+    /// The width is at least 1
+    pub fn set_max_column_width(&mut self, width: usize) {
+        self.max_column_width = Some(width.max(1));
+    }
 
-impl Export for Table {
-    fn to_console(&self) {
-        println!("{}", self);
+    /// Set whether cells wider than the max column width are wrapped to more lines in console,
+    /// the borders of the row span all its lines. They are truncated if not
+    pub fn set_wrap_cells(&mut self, wrap: bool) {
+        self.wrap_cells = wrap;
     }
 
-    //please use to_string_raw() to remove color information
+    /// Set whether colors are shown in console, they are always shown by default
+    pub fn set_color_choice(&mut self, choice: ColorChoice) {
+        self.color_choice = choice;
+    }
 
-    fn to_txt(&self, file: &str, seperation: char) -> Result<(), std::io::Error> {
-        let mut s = String::new();
-        for line in self.0.iter() {
-            s.push_str(line.to_string_raw(seperation).as_str());
-            s.push('\n');
-        }
-        std::fs::write(file, s)
+    /// Set the number of spaces on both sides of each cell in console, default is 1
+    pub fn set_cell_padding(&mut self, padding: usize) {
+        self.cell_padding = padding;
     }
 
-    // fn to_csv(&self, _file: &str) -> Result<(), String> {
-    //     //TODO
-    //     Ok(())
-    // }
+    /// Set the borders in console, default is full borders
+    pub fn set_border_style(&mut self, style: BorderStyle) {
+        self.border_style = style;
+    }
 
-    fn to_excel(&self, file: &str) -> Result<(), XlsxError> {
-        let workbook = Workbook::new(file)?;
-        let mut sheet1 = workbook.add_worksheet(None)?;
-        for (line_num, line) in self.0.iter().enumerate() {
-            for (col_num, cell) in line.0.iter().enumerate() {
-                match &cell.core {
-                    Tablecellcore::String(ref value) => {
-                        sheet1.write_string(line_num as u32, col_num as u16, value, None)?;
-                    }
-                    Tablecellcore::Int(value) => {
-                        sheet1.write_number(
-                            line_num as u32,
-                            col_num as u16,
-                            value.to_f64(),
-                            None,
-                        )?;
-                    }
-                    Tablecellcore::Float(value) => {
-                        sheet1.write_number(line_num as u32, col_num as u16, *value, None)?;
-                    }
-                }
-            }
-        }
-        Ok(())
+    /// Set the text shown for the missing cells at the end of lines shorter than the longest one,
+    /// e.g. `NA`, in console, txt and csv. They are left empty if None
+    pub fn set_empty_placeholder(&mut self, placeholder: Option<String>) {
+        self.empty_placeholder = placeholder;
     }
-}
 
-/* --------------------------------- Display -------------------------------- */
-/// Generate parallel line of a cell with given width, start with +, but not end with +
-fn generate_parallel_line(width: usize) -> String {
-    let mut parallel_line = String::from("\x1b[90m+");
-    parallel_line.push_str("-".repeat(width + 2).as_str());
-    parallel_line
-}
+    /// Set the alignment of the whole table in the target width in console, default is left
+    pub fn set_block_alignment(&mut self, alignment: Alignment) {
+        self.block_alignment = alignment;
+    }
 
-/// Default display mode is left aligned
-impl std::fmt::Display for Table {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut s = String::new();
-        // get the longest row first
-        let width = self.get_longest_row();
+    /// Set the width the table is aligned in, e.g. the width of the terminal.
+    /// The table is not moved if None or it is wider than the target width
+    pub fn set_target_width(&mut self, width: Option<usize>) {
+        self.target_width = width;
+    }
 
-        // get the width of the widest cell in each column in display mode
-        let widths: Vec<usize> = (0..width)
-            .map(|col| {
-                self.0
-                    .iter()
-                    .map(|line| line.get_cell(col).map(|cell| cell.len()).unwrap_or(0))
-                    .max()
-                    .unwrap_or(0)
-            })
-            .collect();
+    /// Show the type of each cell after its value in console, e.g. `3<int>` or `a<str>`,
+    /// like the tags in debug mode
+    pub fn set_show_types(&mut self, show_types: bool) {
+        self.show_types = show_types;
+    }
 
-        // draw proper parallel line with widths
-        let mut parallel_line = String::from("");
-        for width in &widths {
-            parallel_line.push_str(generate_parallel_line(*width).as_str());
+    /// Get a copy of the table whose cells are strings of the value with the type tag,
+    /// keeping the colors, e.g. `1.50<float>` with the float format
+    fn with_type_tags(&self) -> Table {
+        let mut table = self.clone();
+        table.show_types = false;
+        for line in table.lines.iter_mut() {
+            for cell in line.0.iter_mut() {
+                cell.set_core(Tablecellcore::String(format!(
+                    "{}<{}>",
+                    cell.core.to_string_with_format(self.float_format),
+                    cell.core.type_name()
+                )));
+            }
         }
-        parallel_line.push_str("+\x1b[0m\n");
-        s.push_str(&parallel_line);
+        table
+    }
 
-        for line in self.0.iter() {
-            s.push_str(&line.to_string_display(&widths).unwrap().as_str());
-            s.push_str("\n");
-            s.push_str(&parallel_line);
+    /// Get the line padded with the empty placeholder to `width` cells,
+    /// the line itself if there is no placeholder or it is long enough
+    fn pad_line<'a>(&self, line: &'a Tableline, width: usize) -> Cow<'a, Tableline> {
+        match &self.empty_placeholder {
+            Some(placeholder) if line.len() < width => {
+                let mut line = line.clone();
+                while line.len() < width {
+                    line.push_cell(Tablecell::force_as_string(placeholder.clone()));
+                }
+                Cow::Owned(line)
+            }
+            _ => Cow::Borrowed(line),
         }
-        write!(f, "{}", s)
     }
-}
 
-impl std::fmt::Debug for Table {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut s = String::new();
+    /// Get the width of the widest cell in each column in display mode,
+    /// no more than the max column width
+    fn display_widths(&self) -> Vec<usize> {
         // get the longest row first
         let width = self.get_longest_row();
-
-        // get the width of the widest cell in each column in debug mode
-        let widths: Vec<usize> = (0..width)
+        let missing = self
+            .empty_placeholder
+            .as_ref()
+            .map(|placeholder| placeholder.as_str().width())
+            .unwrap_or(0);
+        (0..width)
             .map(|col| {
-                self.0
+                self.lines
                     .iter()
                     .map(|line| {
                         line.get_cell(col)
-                            .map(|cell| format!("{:?}", cell).len())
-                            .unwrap_or(0)
+                            .map(|cell| cell.len_with_format(self.float_format))
+                            .unwrap_or(missing)
                     })
                     .max()
                     .unwrap_or(0)
+                    .min(self.max_column_width.unwrap_or(usize::MAX))
             })
-            .collect();
+            .collect()
+    }
+
+    /// Get the length of longest row of the table
+    pub fn get_longest_row(&self) -> usize {
+        self.lines.iter().map(|line| line.len()).max().unwrap_or(0)
+    }
+
+    /// Check that all the lines have as many cells as the first line,
+    /// return Err with the first line that does not
+    pub fn validate_rectangular(&self) -> Result<(), RaggedRow> {
+        let expected = match self.lines.first() {
+            Some(line) => line.len(),
+            None => return Ok(()),
+        };
+        match self
+            .lines
+            .iter()
+            .enumerate()
+            .find(|(_line_num, line)| line.len() != expected)
+        {
+            Some((line_num, line)) => Err(RaggedRow::new(line_num + 1, line.len(), expected)),
+            None => Ok(()),
+        }
+    }
+
+    /// Push copies of `fill` to the end of every line shorter than the longest one,
+    /// so all the lines have the same length
+    pub fn pad_to_rectangular(&mut self, fill: Tablecell) {
+        let width = self.get_longest_row();
+        for line in self.lines.iter_mut() {
+            while line.len() < width {
+                line.push_cell(fill.clone());
+            }
+        }
+    }
+
+    /// Group the lines by the value of a column, return the first cell of each value and the
+    /// indices of the lines having it, in the order they first appear. Values are the same if
+    /// they have the same type and value like ```filter_by_column()```, e.g. `5<int>` is not
+    /// `5<str>`
+    ///
+    /// Lines that are too short to have the column are ignored
+    fn group_lines(&self, col: usize) -> Vec<(&Tablecell, Vec<usize>)> {
+        let mut groups: Vec<(&Tablecell, Vec<usize>)> = Vec::new();
+        let mut positions: HashMap<(&str, String), usize> = HashMap::new();
+        for (line_num, line) in self.lines.iter().enumerate() {
+            if let Some(cell) = line.get_cell(col) {
+                let key = (cell.core.type_name(), cell.core.to_string());
+                match positions.get(&key) {
+                    Some(&position) => groups[position].1.push(line_num),
+                    None => {
+                        positions.insert(key, groups.len());
+                        groups.push((cell, vec![line_num]));
+                    }
+                }
+            }
+        }
+        groups
+    }
+
+    /// Count each distinct value of a column, return a table of the values and their counts,
+    /// with the most common value first. Values with the same count are in the order they
+    /// first appear, lines too short to have the column are ignored
+    pub fn group_count(&self, col: usize) -> Table {
+        let mut groups = self.group_lines(col);
+        // the sort is stable, so the order of appearance is kept for the same count
+        groups.sort_by_key(|group| std::cmp::Reverse(group.1.len()));
+        groups
+            .into_iter()
+            .map(|(cell, lines)| {
+                Tableline::from_vec(vec![
+                    Tablecell::with_core(cell.core.clone()),
+                    Tablecell::int(lines.len() as i64),
+                ])
+            })
+            .collect()
+    }
+
+    /// Reshape a long-form table to wide form, the distinct values of `row_key` become the
+    /// rows and the distinct values of `col_key` become the columns, both in the order they
+    /// first appear. The cell of a row and a column is the `value` of the line having both
+    /// keys, the last line wins if there are several, and an empty cell if there are none
+    ///
+    /// The first line is the header of the column keys, the first column is the row keys, and
+    /// the cell in the top left corner is empty. Lines too short to have the three columns are
+    /// ignored
+    pub fn pivot(&self, row_key: usize, col_key: usize, value: usize) -> Table {
+        let mut row_keys: Vec<Tablecell> = Vec::new();
+        let mut col_keys: Vec<Tablecell> = Vec::new();
+        let mut row_positions: HashMap<(&str, String), usize> = HashMap::new();
+        let mut col_positions: HashMap<(&str, String), usize> = HashMap::new();
+        let mut values: HashMap<(usize, usize), &Tablecell> = HashMap::new();
+        // get the position of the key, add it to the keys if it is new, the int `1` and
+        // the string `1` are different keys
+        let position = |keys: &mut Vec<Tablecell>,
+                        positions: &mut HashMap<(&str, String), usize>,
+                        cell: &Tablecell| {
+            let key = (cell.core.type_name(), cell.core.to_string());
+            *positions.entry(key).or_insert_with(|| {
+                keys.push(Tablecell::with_core(cell.core.clone()));
+                keys.len() - 1
+            })
+        };
+        for line in self.lines.iter() {
+            if let (Some(row_cell), Some(col_cell), Some(value_cell)) = (
+                line.get_cell(row_key),
+                line.get_cell(col_key),
+                line.get_cell(value),
+            ) {
+                let row = position(&mut row_keys, &mut row_positions, row_cell);
+                let col = position(&mut col_keys, &mut col_positions, col_cell);
+                values.insert((row, col), value_cell);
+            }
+        }
+
+        let header = std::iter::once(Tablecell::empty()).chain(col_keys.iter().cloned());
+        let mut lines = vec![Tableline::from_vec(header.collect())];
+        for (row, row_cell) in row_keys.into_iter().enumerate() {
+            let cells = (0..col_keys.len()).map(|col| match values.get(&(row, col)) {
+                Some(cell) => (*cell).clone(),
+                None => Tablecell::empty(),
+            });
+            lines.push(Tableline::from_vec(
+                std::iter::once(row_cell).chain(cells).collect(),
+            ));
+        }
+        Table::from_vec(lines)
+    }
+
+    /// Check that a column has no duplicate values, return Err with each duplicated value
+    /// and the 1-based numbers of the lines where it appears
+    ///
+    /// Values of different types are not duplicates, e.g. the int `1` and the string `1`.
+    /// Lines that are too short to have the column are ignored
+    pub fn validate_no_duplicate_keys(&self, col: usize) -> Result<(), DuplicateKeys> {
+        let duplicates: Vec<(String, Vec<usize>)> = self
+            .group_lines(col)
+            .into_iter()
+            .filter(|(_cell, lines)| lines.len() > 1)
+            .map(|(cell, lines)| {
+                let lines = lines.iter().map(|line| line + 1).collect();
+                (cell.core.to_string(), lines)
+            })
+            .collect();
+        if duplicates.is_empty() {
+            Ok(())
+        } else {
+            Err(DuplicateKeys::new(col + 1, duplicates))
+        }
+    }
+
+    /// Set the color of a line
+    pub fn set_color_line(&mut self, index: usize, color: OutputColor) {
+        if index >= self.lines.len() {
+            return;
+        }
+        for i in 0..self.lines[index].len() {
+            self.lines[index].get_cell_mut(i).unwrap().set_color(color);
+        }
+    }
+
+    /// Set the color of a column
+    pub fn set_color_column(&mut self, index: usize, color: OutputColor) {
+        for i in 0..self.lines.len() {
+            if let Some(cell) = self.lines[i].get_cell_mut(index) {
+                cell.set_color(color);
+            }
+        }
+    }
+
+    /// Set the color of every cell for which `f` returns a color, other cells are unchanged
+    pub fn color_cells_if<F: Fn(&Tablecell) -> Option<OutputColor>>(&mut self, f: F) {
+        for line in self.lines.iter_mut() {
+            for cell in line.0.iter_mut() {
+                if let Some(color) = f(cell) {
+                    cell.set_color(color);
+                }
+            }
+        }
+    }
+
+    /// Get the `(row, col)` of every cell for which `f` returns true, line by line
+    pub fn find_all<F: Fn(&Tablecell) -> bool>(&self, f: F) -> Vec<(usize, usize)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.0
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, cell)| f(cell))
+                    .map(move |(col, _)| (row, col))
+            })
+            .collect()
+    }
+
+    /// Get the `(row, col)` of the first cell for which `f` returns true, line by line
+    pub fn find_first<F: Fn(&Tablecell) -> bool>(&self, f: F) -> Option<(usize, usize)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .find_map(|(row, line)| line.0.iter().position(&f).map(|col| (row, col)))
+    }
+
+    /// Get the `(row, col)` of every cell whose value as string matches the regex, line by line.
+    /// The value is matched without color, e.g. `1.5` for a float
+    pub fn find_by_regex(&self, re: &Regex) -> Vec<(usize, usize)> {
+        self.find_all(|cell| re.is_match(cell.core.to_string().as_str()))
+    }
+
+    /// Replace every match of the pattern in the string cells with `replacement`, which can
+    /// refer to the groups like `$1`. Return the number of cells changed
+    ///
+    /// The type of a changed cell is decided again, e.g. `1,000` becomes an int after removing
+    /// `,`. The colors are kept and other cells are not touched
+    pub fn replace_all(&mut self, pattern: &Regex, replacement: &str) -> usize {
+        let mut changed = 0;
+        for cell in self.lines.iter_mut().flat_map(|line| line.0.iter_mut()) {
+            if let Tablecellcore::String(value) = &cell.core {
+                let replaced = pattern.replace_all(value, replacement);
+                if replaced != *value {
+                    *cell = Tablecell {
+                        color: cell.color,
+                        background: cell.background,
+                        ..Tablecell::auto_from(replaced.into_owned())
+                    };
+                    changed += 1;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Set the color of a cell, return Err if the index is out of range
+    pub fn set_color_cell(
+        &mut self,
+        (row, col): (usize, usize),
+        color: OutputColor,
+    ) -> Result<(), String> {
+        match self
+            .lines
+            .get_mut(row)
+            .and_then(|line| line.get_cell_mut(col))
+        {
+            Some(cell) => {
+                cell.set_color(color);
+                Ok(())
+            }
+            None => Err("Index out of range".to_string()),
+        }
+    }
+
+    /// Set the label of a color shown in the color legend, e.g. `negative` for red
+    pub fn set_color_label(&mut self, color: OutputColor, label: String) {
+        match self.color_labels.iter_mut().find(|(c, _)| *c == color) {
+            Some((_, old)) => *old = label,
+            None => self.color_labels.push((color, label)),
+        }
+    }
+
+    /// Get a legend of the colors used in the table to print below it, one color a line with
+    /// its label if set, foreground colors first and then background colors, each in the order
+    /// they first appear. The default black foreground is not listed, empty if no color is used
+    pub fn color_legend(&self) -> String {
+        let mut foregrounds: Vec<OutputColor> = Vec::new();
+        let mut backgrounds: Vec<OutputColor> = Vec::new();
+        for cell in self.lines.iter().flat_map(|line| line.0.iter()) {
+            if cell.color != OutputColor::Black && !foregrounds.contains(&cell.color) {
+                foregrounds.push(cell.color);
+            }
+            if let Some(background) = cell.background {
+                if !backgrounds.contains(&background) {
+                    backgrounds.push(background);
+                }
+            }
+        }
+        let label = |color: OutputColor| {
+            self.color_labels
+                .iter()
+                .find(|(c, _)| *c == color)
+                .map(|(_, label)| format!(": {}", label))
+                .unwrap_or_default()
+        };
+        let mut s = String::new();
+        for color in foregrounds {
+            let mut sample = Tablecell::force_as_string(color.to_string());
+            sample.set_color(color);
+            s.push_str(&format!("{}{}\n", sample, label(color)));
+        }
+        for color in backgrounds {
+            let mut sample = Tablecell::force_as_string(format!("{} background", color));
+            sample.set_background(color);
+            s.push_str(&format!("{}{}\n", sample, label(color)));
+        }
+        if !self.color_choice.use_color() {
+            s = ANSI_ESCAPE.replace_all(&s, "").to_string();
+        }
+        s
+    }
+
+    /// Merge the cells from `col_start` to `col_end` (both included) of a row when rendering,
+    /// the merged cell shows the text of the first one, e.g. a header covering several columns
+    ///
+    /// Return Err if the range is out of range or overlaps with another span of the row.
+    /// Spans follow the lines when lines are inserted, removed or swapped, and are dropped when their
+    /// lines or columns are removed or their columns are moved. They are not changed when
+    /// the cells of a line are edited directly, the spans out of range are skipped in exports
+    pub fn merge_cells_display(
+        &mut self,
+        row: usize,
+        col_start: usize,
+        col_end: usize,
+    ) -> Result<(), String> {
+        if row >= self.lines.len() || col_start > col_end || col_end >= self.lines[row].len() {
+            return Err("Index out of range".to_string());
+        }
+        if self
+            .spans
+            .iter()
+            .any(|(r, start, end)| *r == row && col_start <= *end && *start <= col_end)
+        {
+            return Err("Overlaps with another span".to_string());
+        }
+        self.spans.push((row, col_start, col_end));
+        Ok(())
+    }
+
+    /// Get the spans of merged cells, `(row, col_start, col_end)` with both ends included
+    pub fn spans(&self) -> &[(usize, usize, usize)] {
+        &self.spans
+    }
+
+    /// Move the spans to the new row given by `f`, the spans are dropped if it returns None
+    fn remap_span_rows<F: Fn(usize) -> Option<usize>>(&mut self, f: F) {
+        self.spans = self
+            .spans
+            .iter()
+            .filter_map(|(row, start, end)| f(*row).map(|row| (row, *start, *end)))
+            .collect();
+    }
+}
+
+/* -------------------------------- Aggregate ------------------------------- */
+
+impl Table {
+    /// Get the int and float cells of a column, other cells are ignored
+    fn column_numbers(&self, col: usize) -> Vec<&Tablecellcore> {
+        self.get_column(col)
+            .into_iter()
+            .flatten()
+            .map(|cell| &cell.core)
+            .filter(|core| matches!(core, Tablecellcore::Int(_) | Tablecellcore::Float(_)))
+            .collect()
+    }
+
+    /// Get the sum of the ints and floats of a column, it is an int if all of them are ints.
+    /// Return None if the column has no number
+    pub fn column_sum(&self, col: usize) -> Option<Tablecellcore> {
+        let numbers = self.column_numbers(col);
+        if numbers.is_empty() {
+            return None;
+        }
+        let mut int_sum = IBig::from(0);
+        let mut float_sum = 0.0;
+        let mut is_int = true;
+        for number in numbers {
+            match number {
+                Tablecellcore::Int(value) => int_sum += value,
+                Tablecellcore::Float(value) => {
+                    float_sum += value;
+                    is_int = false;
+                }
+                _ => unreachable!(),
+            }
+        }
+        if is_int {
+            Some(Tablecellcore::Int(int_sum))
+        } else {
+            Some(Tablecellcore::Float(int_sum.to_f64() + float_sum))
+        }
+    }
+
+    /// Get the mean of the ints and floats of a column as a float.
+    /// Return None if the column has no number
+    pub fn column_mean(&self, col: usize) -> Option<Tablecellcore> {
+        let count = self.column_numbers(col).len();
+        self.column_sum(col)
+            .map(|sum| Tablecellcore::Float(number_value(&sum) / count as f64))
+    }
+
+    /// Get the smallest of the ints and floats of a column, the first one if there are more.
+    /// Return None if the column has no number
+    pub fn column_min(&self, col: usize) -> Option<Tablecellcore> {
+        self.column_numbers(col)
+            .into_iter()
+            .reduce(|min, number| {
+                if number_value(number) < number_value(min) {
+                    number
+                } else {
+                    min
+                }
+            })
+            .cloned()
+    }
+
+    /// Get the largest of the ints and floats of a column, the first one if there are more.
+    /// Return None if the column has no number
+    pub fn column_max(&self, col: usize) -> Option<Tablecellcore> {
+        self.column_numbers(col)
+            .into_iter()
+            .reduce(|max, number| {
+                if number_value(number) > number_value(max) {
+                    number
+                } else {
+                    max
+                }
+            })
+            .cloned()
+    }
+}
+
+/// The aggregate shown in a column of the summary row, see ```Table::with_summary_row()```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryOp {
+    /// The sum of the numbers
+    Sum,
+    /// The mean of the numbers
+    Mean,
+    /// The number of numbers
+    Count,
+    /// An empty cell
+    None,
+}
+
+impl Table {
+    /// Get a copy of the table with a summary row appended, the `i`th cell of the row is the
+    /// aggregate `ops[i]` of the `i`th column. Columns without an op or without any number
+    /// get an empty cell. The summary row is the last line, color it by ```set_color_line()```
+    pub fn with_summary_row(&self, ops: &[SummaryOp]) -> Table {
+        let width = self.get_longest_row();
+        let cells: Vec<Tablecell> = (0..width)
+            .map(|col| {
+                let core = match ops.get(col).copied().unwrap_or(SummaryOp::None) {
+                    SummaryOp::Sum => self.column_sum(col),
+                    SummaryOp::Mean => self.column_mean(col),
+                    SummaryOp::Count => Some(Tablecellcore::Int(IBig::from(
+                        self.column_numbers(col).len(),
+                    ))),
+                    SummaryOp::None => None,
+                };
+                let mut cell = Tablecell::force_as_string(String::new());
+                if let Some(core) = core {
+                    cell.core = core;
+                }
+                cell
+            })
+            .collect();
+        let mut table = self.clone();
+        table.push_line(Tableline::from_vec(cells));
+        table
+    }
+}
+
+/// Get the value of an int or float as f64
+fn number_value(core: &Tablecellcore) -> f64 {
+    match core {
+        Tablecellcore::Int(value) => value.to_f64(),
+        Tablecellcore::Float(value) => *value,
+        _ => f64::NAN,
+    }
+}
+
+/* --------------------------------- Export --------------------------------- */
+
+impl Export for Table {
+    fn to_console(&self) {
+        println!("{}", self);
+    }
+
+    //please use to_string_raw() to remove color information
+
+    fn to_txt(&self, file: &str, seperation: char) -> Result<(), std::io::Error> {
+        let mut buffer = Vec::new();
+        self.write_txt(&mut buffer, seperation)?;
+        std::fs::write(file, buffer)
+    }
+
+    /// Lines shorter than the longest one are padded with empty fields
+    fn to_csv(&self, file: &str) -> Result<(), std::io::Error> {
+        let mut buffer = Vec::new();
+        self.write_csv(&mut buffer)?;
+        std::fs::write(file, buffer)
+    }
+
+    fn to_excel(&self, file: &str) -> Result<(), XlsxError> {
+        let workbook = Workbook::new(file)?;
+        let mut sheet1 = workbook.add_worksheet(None)?;
+        for (line_num, line) in self.lines.iter().enumerate() {
+            for (col_num, cell) in line.0.iter().enumerate() {
+                match &cell.core {
+                    Tablecellcore::String(ref value) => {
+                        sheet1.write_string(line_num as u32, col_num as u16, value, None)?;
+                    }
+                    Tablecellcore::Int(value) => {
+                        sheet1.write_number(
+                            line_num as u32,
+                            col_num as u16,
+                            value.to_f64(),
+                            None,
+                        )?;
+                    }
+                    Tablecellcore::Float(value) => {
+                        sheet1.write_number(line_num as u32, col_num as u16, *value, None)?;
+                    }
+                    Tablecellcore::Bool(value) => {
+                        sheet1.write_boolean(line_num as u32, col_num as u16, *value, None)?;
+                    }
+                    Tablecellcore::Percent(value) => {
+                        sheet1.write_number(line_num as u32, col_num as u16, *value, None)?;
+                    }
+                    Tablecellcore::Rational(numerator, denominator) => {
+                        sheet1.write_number(
+                            line_num as u32,
+                            col_num as u16,
+                            numerator.to_f64() / denominator.to_f64(),
+                            None,
+                        )?;
+                    }
+                    // a missing value is left blank
+                    Tablecellcore::Empty => {}
+                }
+            }
+        }
+        for (row, col_start, col_end) in self.spans.iter() {
+            // the span is out of range if the cells of the line are removed directly
+            let Some(line) = self.lines.get(*row).filter(|line| *col_end < line.len()) else {
+                continue;
+            };
+            let text = line.get_cell(*col_start).unwrap().core.to_string();
+            sheet1.merge_range(
+                *row as u32,
+                *col_start as u16,
+                *row as u32,
+                *col_end as u16,
+                text.as_str(),
+                None,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/* ----------------------------------- Tsv ---------------------------------- */
+
+impl Table {
+    /// Write the table to a tab seperated file, which can be read back by ```from_tsv()```.
+    /// Cells are never quoted
+    ///
+    /// Return InvalidData error if a cell contains a tab or line break, the file is not written
+    pub fn to_tsv(&self, file: &str) -> Result<(), std::io::Error> {
+        let mut buffer = Vec::new();
+        self.write_tsv(&mut buffer)?;
+        std::fs::write(file, buffer)
+    }
+}
+
+/* --------------------------------- Writer --------------------------------- */
+
+/// Write the bytes to the end of a file, create the file if it does not exist
+fn append_to_file(file: &str, bytes: &[u8]) -> Result<(), std::io::Error> {
+    use std::io::Write;
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file)?
+        .write_all(bytes)
+}
+
+impl Table {
+    /// Write the table to a writer like ```to_txt()```, e.g. a `Vec<u8>` or stdout
+    pub fn write_txt<W: std::io::Write>(
+        &self,
+        w: &mut W,
+        seperation: char,
+    ) -> Result<(), std::io::Error> {
+        let width = self.get_longest_row();
+        for line in self.lines.iter() {
+            writeln!(
+                w,
+                "{}",
+                self.pad_line(line, width).to_string_raw(seperation)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the table to a writer like ```to_csv()```, lines shorter than the longest one
+    /// are padded with empty fields, or the empty placeholder if it is set
+    pub fn write_csv<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
+        let width = self.get_longest_row();
+        let missing = self
+            .empty_placeholder
+            .as_ref()
+            .map(|placeholder| escape_csv_field(placeholder))
+            .unwrap_or_default();
+        for line in self.lines.iter() {
+            let fields: Vec<String> = (0..width)
+                .map(|col| {
+                    line.get_cell(col)
+                        .map(|cell| escape_csv_field(cell.core.to_string().as_str()))
+                        .unwrap_or_else(|| missing.clone())
+                })
+                .collect();
+            writeln!(w, "{}", fields.join(","))?;
+        }
+        Ok(())
+    }
+
+    /// Append the lines of the table to the end of a file like ```to_txt()```, e.g. a new batch
+    /// of a long running job. The file is created if it does not exist
+    pub fn append_txt(&self, file: &str, seperation: char) -> Result<(), std::io::Error> {
+        let mut buffer = Vec::new();
+        self.write_txt(&mut buffer, seperation)?;
+        append_to_file(file, &buffer)
+    }
+
+    /// Append the lines of the table to the end of a file like ```to_csv()```, e.g. a new batch
+    /// of a long running job. The file is created if it does not exist
+    pub fn append_csv(&self, file: &str) -> Result<(), std::io::Error> {
+        let mut buffer = Vec::new();
+        self.write_csv(&mut buffer)?;
+        append_to_file(file, &buffer)
+    }
+
+    /// Write the table to a writer like ```to_tsv()```
+    ///
+    /// Return InvalidData error if a cell contains a tab or line break, nothing is written then
+    pub fn write_tsv<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
+        for (line_num, line) in self.lines.iter().enumerate() {
+            for (col_num, cell) in line.0.iter().enumerate() {
+                if cell.core.to_string().contains(['\t', '\n', '\r']) {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "cell at line {}, column {} contains a tab or line break",
+                            line_num + 1,
+                            col_num + 1
+                        ),
+                    ));
+                }
+            }
+        }
+        for line in self.lines.iter() {
+            let fields: Vec<String> = line.0.iter().map(|cell| cell.core.to_string()).collect();
+            writeln!(w, "{}", fields.join("\t"))?;
+        }
+        Ok(())
+    }
+}
+
+/* ----------------------------------- Svg ---------------------------------- */
+
+/// Width of a character of the monospace font in svg, in pixels
+const SVG_CHAR_WIDTH: usize = 8;
+/// Height of a line in svg, in pixels
+const SVG_LINE_HEIGHT: usize = 20;
+
+impl Table {
+    /// Render the table to a svg image, each cell is a `<rect>` with a `<text>` in the
+    /// color of the cell. Columns are sized like the console display with a monospace font
+    pub fn to_svg(&self) -> String {
+        let widths: Vec<usize> = self
+            .display_widths()
+            .iter()
+            .map(|width| (width + 2) * SVG_CHAR_WIDTH)
+            .collect();
+        let mut offsets = Vec::with_capacity(widths.len());
+        let mut total_width = 0;
+        for width in &widths {
+            offsets.push(total_width);
+            total_width += width;
+        }
+
+        let mut s = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             font-family=\"monospace\" font-size=\"13\">\n",
+            total_width,
+            self.lines.len() * SVG_LINE_HEIGHT
+        );
+        for (line_num, line) in self.lines.iter().enumerate() {
+            let y = line_num * SVG_LINE_HEIGHT;
+            for (col_num, cell) in line.0.iter().enumerate() {
+                // the cell without a background is white, like the page
+                let background = cell
+                    .background
+                    .map(css_color)
+                    .unwrap_or("white".to_string());
+                s.push_str(
+                    format!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"grey\"/>\n",
+                        offsets[col_num], y, widths[col_num], SVG_LINE_HEIGHT, background
+                    )
+                    .as_str(),
+                );
+                s.push_str(
+                    format!(
+                        "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>\n",
+                        offsets[col_num] + SVG_CHAR_WIDTH,
+                        y + SVG_LINE_HEIGHT - 6,
+                        css_color(cell.color),
+                        escape_xml(cell.core.to_string().as_str())
+                    )
+                    .as_str(),
+                );
+            }
+        }
+        s.push_str("</svg>\n");
+        s
+    }
+}
+
+/* ------------------------------- Debug dump ------------------------------- */
+
+impl Table {
+    /// Dump every cell as a json object in a line, with its position, the text it is parsed
+    /// from, type and value, which is used to find out how the input is parsed
+    pub fn debug_dump(&self) -> String {
+        let mut s = String::new();
+        for (row, line) in self.lines.iter().enumerate() {
+            for (col, cell) in line.0.iter().enumerate() {
+                let raw = cell.raw();
+                let value = match &cell.core {
+                    Tablecellcore::Int(v) => v.to_string(),
+                    Tablecellcore::Float(v) if v.is_finite() => v.to_string(),
+                    Tablecellcore::Percent(v) => v.to_string(),
+                    Tablecellcore::Bool(v) => v.to_string(),
+                    Tablecellcore::Empty => "null".to_string(),
+                    _ => format!("\"{}\"", escape_json(cell.core.to_string().as_str())),
+                };
+                s.push_str(
+                    format!(
+                        "{{\"row\":{},\"col\":{},\"raw\":\"{}\",\"type\":\"{}\",\"value\":{}}}\n",
+                        row,
+                        col,
+                        escape_json(raw.as_str()),
+                        cell.core.type_name(),
+                        value
+                    )
+                    .as_str(),
+                );
+            }
+        }
+        s
+    }
+}
+
+/// Escape the characters that are special in a json string
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(format!("\\u{:04x}", c as u32).as_str()),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/* ---------------------------------- Html ---------------------------------- */
+
+impl Table {
+    /// Write the table to a html file, see ```to_html_string()```
+    pub fn to_html(&self, file: &str) -> Result<(), std::io::Error> {
+        std::fs::write(file, self.to_html_string())
+    }
+
+    /// Render the table to a `<style>` block and a html `<table>`, merged cells are rendered
+    /// with `colspan`. Each `<td>` has a class of its color like `c-red`, a class of its
+    /// background like `bg-blue` if it has one, and `num` if it is a number
+    pub fn to_html_string(&self) -> String {
+        // css rules of the classes used, in the order they first appear
+        let mut rules: Vec<String> = vec![".num { text-align: right; }".to_string()];
+        let mut add_rule = |rule: String| {
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+        };
+        let mut body = String::from("<table>\n");
+        for (line_num, line) in self.lines.iter().enumerate() {
+            body.push_str("<tr>");
+            let mut col_num = 0;
+            while col_num < line.len() {
+                let cell = line.get_cell(col_num).unwrap();
+                let text = escape_xml(cell.core.to_string().as_str());
+                let mut classes = vec![format!("c-{}", css_class_name(cell.color))];
+                add_rule(format!(
+                    ".c-{} {{ color: {}; }}",
+                    css_class_name(cell.color),
+                    css_color(cell.color)
+                ));
+                if let Some(background) = cell.background {
+                    classes.push(format!("bg-{}", css_class_name(background)));
+                    add_rule(format!(
+                        ".bg-{} {{ background-color: {}; }}",
+                        css_class_name(background),
+                        css_color(background)
+                    ));
+                }
+                if !matches!(
+                    cell.core,
+                    Tablecellcore::String(_) | Tablecellcore::Bool(_) | Tablecellcore::Empty
+                ) {
+                    classes.push("num".to_string());
+                }
+                let class = classes.join(" ");
+                match self
+                    .spans
+                    .iter()
+                    .find(|(row, start, _end)| *row == line_num && *start == col_num)
+                {
+                    Some((_row, start, end)) => {
+                        body.push_str(
+                            format!(
+                                "<td class=\"{}\" colspan=\"{}\">{}</td>",
+                                class,
+                                end - start + 1,
+                                text
+                            )
+                            .as_str(),
+                        );
+                        col_num = end + 1;
+                    }
+                    None => {
+                        body.push_str(format!("<td class=\"{}\">{}</td>", class, text).as_str());
+                        col_num += 1;
+                    }
+                }
+            }
+            body.push_str("</tr>\n");
+        }
+        body.push_str("</table>\n");
+        format!("<style>\n{}\n</style>\n{}", rules.join("\n"), body)
+    }
+}
+
+/* ---------------------------------- Latex --------------------------------- */
+
+impl Table {
+    /// Write the table to a latex file, see ```to_latex_string()```
+    pub fn to_latex(&self, file: &str) -> Result<(), std::io::Error> {
+        std::fs::write(file, self.to_latex_string())
+    }
+
+    /// Render the table to a latex `tabular` with a `\hline` between every two lines like the
+    /// console display. A column is `r` if all its cells are numbers and `l` otherwise,
+    /// merged cells are rendered with `\multicolumn` and short lines are padded with empty cells
+    pub fn to_latex_string(&self) -> String {
+        let width = self.get_longest_row();
+        let spec: String = (0..width)
+            .map(|col| {
+                let numeric = self.get_column(col).iter().flatten().all(|cell| {
+                    !matches!(cell.core, Tablecellcore::String(_) | Tablecellcore::Bool(_))
+                });
+                if numeric {
+                    'r'
+                } else {
+                    'l'
+                }
+            })
+            .collect();
+        let mut s = format!("\\begin{{tabular}}{{{}}}\n\\hline\n", spec);
+        for (line_num, line) in self.lines.iter().enumerate() {
+            let mut fields = Vec::with_capacity(width);
+            let mut col_num = 0;
+            while col_num < width {
+                let text = line
+                    .get_cell(col_num)
+                    .map(|cell| escape_latex(cell.core.to_string().as_str()))
+                    .unwrap_or_default();
+                match self
+                    .spans
+                    .iter()
+                    .find(|(row, start, _end)| *row == line_num && *start == col_num)
+                {
+                    Some((_row, start, end)) => {
+                        fields.push(format!(
+                            "\\multicolumn{{{}}}{{{}}}{{{}}}",
+                            end - start + 1,
+                            &spec[*start..*start + 1],
+                            text
+                        ));
+                        col_num = end + 1;
+                    }
+                    None => {
+                        fields.push(text);
+                        col_num += 1;
+                    }
+                }
+            }
+            s.push_str(fields.join(" & ").as_str());
+            s.push_str(" \\\\\n\\hline\n");
+        }
+        s.push_str("\\end{tabular}\n");
+        s
+    }
+}
+
+/// Escape the characters that are special in latex
+fn escape_latex(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Compile the seperation to a regex, return ArgError if it is invalid
+fn seperation_regex(seperation: &str) -> Result<Regex, Box<ArgError>> {
+    Regex::new(seperation).map_err(|e| {
+        Box::new(ArgError::new(
+            ArgErrorKind::WrongFormat,
+            Some(format!("the regex is invalid: {}", e)),
+            Some(seperation.to_string()),
+            Some(seperation.to_string()),
+            Some((0, seperation.len())),
+            None,
+        ))
+    })
+}
+
+/// Remove all the '\n' and '\r' from the input if `end_line` is not '\n', so the lines of a
+/// file split by a custom `end_line` have no line break in cells. Those in `end_line` are kept
+fn remove_line_breaks(s: String, end_line: &str) -> String {
+    if end_line.contains('\n') {
+        return s;
+    }
+    if end_line.contains('\r') {
+        s.replace('\n', "")
+    } else {
+        s.replace(['\n', '\r'], "")
+    }
+}
+
+/// The cells of a forced line or column that couldn't be forced to the type
+struct ForceParseFailure {
+    /// 1-based number of the line or column
+    number: usize,
+    force_type: setting::ForceType,
+    count: usize,
+    first_value: String,
+}
+
+impl ForceParseFailure {
+    /// Make a warning of the failure, e.g. "column 3" couldn't be forced to int for 2 cell(s)
+    fn to_warning(&self, line_column: setting::LineColumn) -> ArgError {
+        let line_column = match line_column {
+            setting::LineColumn::Line => "line",
+            setting::LineColumn::Column => "column",
+        };
+        ArgError::new(
+            ArgErrorKind::ForceParseFailed,
+            Some(format!(
+                "couldn't be forced to {} for {} cell(s), e.g. \"{}\", used auto",
+                self.force_type.type_name(),
+                self.count,
+                self.first_value
+            )),
+            Some(format!("{} {}", line_column, self.number)),
+            Some("--force-parse".to_string()),
+            None,
+            None,
+        )
+    }
+}
+
+/// Record the cell in the failures if it is not of the forced type, `number` is the 1-based
+/// number of the line or column it is in. Empty cells are never recorded
+fn record_force_failure(
+    failures: &mut Vec<ForceParseFailure>,
+    number: usize,
+    force_type: setting::ForceType,
+    cell: &Tablecell,
+) {
+    if cell.core.type_name() == force_type.type_name() || cell.core.to_string().is_empty() {
+        return;
+    }
+    match failures.iter_mut().find(|failure| failure.number == number) {
+        Some(failure) => failure.count += 1,
+        None => failures.push(ForceParseFailure {
+            number,
+            force_type,
+            count: 1,
+            first_value: cell.core.to_string(),
+        }),
+    }
+}
+
+/// Get the css color of the cell color, e.g. `red` or `#ff8000`
+fn css_color(color: OutputColor) -> String {
+    match color {
+        OutputColor::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        OutputColor::Ansi256(n) => {
+            // the 16 system colors, a 6x6x6 color cube and 24 greys, as in xterm
+            const SYSTEM: [(u8, u8, u8); 16] = [
+                (0, 0, 0),
+                (128, 0, 0),
+                (0, 128, 0),
+                (128, 128, 0),
+                (0, 0, 128),
+                (128, 0, 128),
+                (0, 128, 128),
+                (192, 192, 192),
+                (128, 128, 128),
+                (255, 0, 0),
+                (0, 255, 0),
+                (255, 255, 0),
+                (0, 0, 255),
+                (255, 0, 255),
+                (0, 255, 255),
+                (255, 255, 255),
+            ];
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let (r, g, b) = match n {
+                0..=15 => SYSTEM[n as usize],
+                16..=231 => {
+                    let v = n - 16;
+                    (level(v / 36), level(v / 6 % 6), level(v % 6))
+                }
+                _ => {
+                    let grey = 8 + (n - 232) * 10;
+                    (grey, grey, grey)
+                }
+            };
+            css_color(OutputColor::Rgb(r, g, b))
+        }
+        _ => color.to_string().to_lowercase(),
+    }
+}
+
+/// Get the name of the color used in css classes, e.g. `red`, `ff8000` or `196`
+fn css_class_name(color: OutputColor) -> String {
+    match color {
+        OutputColor::Rgb(r, g, b) => format!("{:02x}{:02x}{:02x}", r, g, b),
+        OutputColor::Ansi256(n) => n.to_string(),
+        _ => color.to_string().to_lowercase(),
+    }
+}
+
+/// Escape the characters that are special in xml
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Quote a csv field if it contains comma, quote or line break, quotes inside are doubled
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/* ---------------------------------- Index --------------------------------- */
+
+/// Get the cell at `(row, col)`, panic if it is out of range like `Vec`,
+/// use ```get_cell()``` to get an Option instead
+impl std::ops::Index<(usize, usize)> for Table {
+    type Output = Tablecell;
+    fn index(&self, (row, col): (usize, usize)) -> &Tablecell {
+        &self.lines[row].0[col]
+    }
+}
+
+impl std::ops::IndexMut<(usize, usize)> for Table {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Tablecell {
+        &mut self.lines[row].0[col]
+    }
+}
+
+/* -------------------------------- Iterator -------------------------------- */
+
+/// Collect tablelines to a table, e.g. the lines of ```read_from_file_streaming()```
+impl FromIterator<Tableline> for Table {
+    fn from_iter<I: IntoIterator<Item = Tableline>>(iter: I) -> Self {
+        Table::from_vec(iter.into_iter().collect())
+    }
+}
+
+/// Push the tablelines to the end of the table
+impl Extend<Tableline> for Table {
+    fn extend<I: IntoIterator<Item = Tableline>>(&mut self, iter: I) {
+        self.lines.extend(iter);
+    }
+}
+
+/* --------------------------------- Display -------------------------------- */
+/// Generate parallel line of a cell with given width and padding, start with +, but not end with +
+fn generate_parallel_line(width: usize, padding: usize) -> String {
+    let mut parallel_line = String::from("\x1b[90m+");
+    parallel_line.push_str("-".repeat(width + 2 * padding).as_str());
+    parallel_line
+}
+
+/// Default display mode is left aligned
+impl std::fmt::Display for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.show_types {
+            return write!(f, "{}", self.with_type_tags());
+        }
+        let mut s = String::new();
+        let widths = self.display_widths();
+
+        // draw proper parallel line with widths
+        let mut parallel_line = String::from("");
+        for width in &widths {
+            parallel_line.push_str(generate_parallel_line(*width, self.cell_padding).as_str());
+        }
+        parallel_line.push_str("+\x1b[0m\n");
+        let minimal = self.border_style == BorderStyle::Minimal;
+        if minimal {
+            parallel_line = parallel_line.replace('+', "-");
+        }
+        s.push_str(&parallel_line);
+
+        for (line_num, line) in self.lines.iter().enumerate() {
+            let line = self.pad_line(line, widths.len());
+            if self.wrap_cells {
+                for physical_line in line
+                    .to_string_display_wrapped(
+                        &widths,
+                        self.float_format,
+                        self.cell_padding,
+                        self.border_style,
+                    )
+                    .unwrap()
+                {
+                    s.push_str(physical_line.as_str());
+                    s.push('\n');
+                }
+            } else {
+                s.push_str(
+                    line.to_string_display(
+                        &widths,
+                        self.float_format,
+                        self.cell_padding,
+                        self.border_style,
+                    )
+                    .unwrap()
+                    .as_str(),
+                );
+                s.push('\n');
+            }
+            // the minimal borders only have rules below the first line and at the bottom
+            if !minimal || line_num == 0 || line_num + 1 == self.lines.len() {
+                s.push_str(&parallel_line);
+            }
+        }
+        // move the whole table right by the blank in the target width
+        let table_width = ANSI_ESCAPE
+            .replace_all(&parallel_line, "")
+            .trim_end()
+            .width();
+        let blank = self.target_width.unwrap_or(0).saturating_sub(table_width);
+        let indent = match self.block_alignment {
+            Alignment::Left => 0,
+            Alignment::Center => blank / 2,
+            Alignment::Right => blank,
+        };
+        if indent > 0 {
+            let indent = " ".repeat(indent);
+            s = s
+                .lines()
+                .map(|line| format!("{}{}\n", indent, line))
+                .collect();
+        }
+        if !self.color_choice.use_color() {
+            s = ANSI_ESCAPE.replace_all(&s, "").to_string();
+        }
+        write!(f, "{}", s)
+    }
+}
+
+/// Regex of the ansi escape codes of colors
+static ANSI_ESCAPE: Lazy<Regex> = Lazy::new(|| Regex::new("\x1b\\[[0-9;]*m").unwrap());
+
+impl std::fmt::Debug for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = String::new();
+        // get the longest row first
+        let width = self.get_longest_row();
+
+        // get the width of the widest cell in each column in debug mode
+        let widths: Vec<usize> = (0..width)
+            .map(|col| {
+                self.lines
+                    .iter()
+                    .map(|line| {
+                        line.get_cell(col)
+                            .map(|cell| format!("{:?}", cell).len())
+                            .unwrap_or(0)
+                    })
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
 
         // draw proper parallel line with widths
         let mut parallel_line = String::from("");
         for width in &widths {
-            parallel_line.push_str(generate_parallel_line(*width).as_str());
+            parallel_line.push_str(generate_parallel_line(*width, 1).as_str());
         }
         parallel_line.push_str("+\x1b[0m\n");
         s.push_str(&parallel_line);
 
-        for line in self.0.iter() {
-            s.push_str(&line.to_string_debug(&widths).unwrap().as_str());
-            s.push_str("\n");
-            s.push_str(&parallel_line);
+        for line in self.lines.iter() {
+            s.push_str(&line.to_string_debug(&widths).unwrap().as_str());
+            s.push('\n');
+            s.push_str(&parallel_line);
+        }
+        write!(f, "{}", s)
+    }
+}
+
+/* ---------------------------------- tests --------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{ErrorLevel, ErrorType};
+    use ibig::IBig;
+
+    #[test]
+    fn test_from_string_simple() {
+        let s = "1,2223,3\n4,5,6\n7,8,9".to_string();
+        let table = Table::from_string(s, ",", "\n");
+        println!("{:?}", table);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.get_line(0).unwrap().len(), 3);
+        assert_eq!(table.get_line(1).unwrap().len(), 3);
+        assert_eq!(table.get_line(2).unwrap().len(), 3);
+        assert_eq!(table.get_cell((0, 0)).unwrap().to_string(), "1");
+        assert_eq!(table.get_cell((0, 1)).unwrap().to_string(), "2223");
+        assert_eq!(table.get_cell((0, 2)).unwrap().to_string(), "3");
+        assert_eq!(table.get_cell((1, 0)).unwrap().to_string(), "4");
+        assert_eq!(table.get_cell((1, 1)).unwrap().to_string(), "5");
+        assert_eq!(table.get_cell((1, 2)).unwrap().to_string(), "6");
+        assert_eq!(table.get_cell((2, 0)).unwrap().to_string(), "7");
+        assert_eq!(table.get_cell((2, 1)).unwrap().to_string(), "8");
+        assert_eq!(table.get_cell((2, 2)).unwrap().to_string(), "9");
+    }
+
+    #[test]
+    fn test_partial_eq() {
+        let s = "a 1 2.5\nb 2 NaN";
+        let table_a = Table::from_string(s.to_string(), " ", "\n");
+        let table_b = Table::from_string(s.to_string(), " ", "\n");
+        assert_eq!(table_a, table_b);
+
+        let table_c = Table::from_string("a 1 2.5\nb 2 3".to_string(), " ", "\n");
+        assert_ne!(table_a, table_c);
+        let mut table_d = Table::from_string(s.to_string(), " ", "\n");
+        table_d.set_color_line(0, OutputColor::Red);
+        assert_ne!(table_a, table_d);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let s = "a 1 2.5 true\nb 123456789012345678901234567890 -3 false";
+        let mut table = Table::from_string(s.to_string(), " ", "\n");
+        table.set_color_line(1, OutputColor::Red);
+        table.merge_cells_display(0, 1, 2).unwrap();
+        let json = serde_json::to_string(&table).unwrap();
+        assert!(json.contains(r#"{"Int":"123456789012345678901234567890"}"#));
+        let back: Table = serde_json::from_str(json.as_str()).unwrap();
+        assert_eq!(table, back);
+    }
+
+    #[test]
+    fn test_to_txt() {
+        let s = "1,2223,3\n4,5,6\n7,8,9".to_string();
+        let table = Table::from_string(s, ",", "\n");
+        table.to_txt("test.txt", ',').unwrap();
+        let s = std::fs::read_to_string("test.txt").unwrap();
+        let table = Table::from_string(s, ",", "\n");
+        println!("{:?}", table);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.get_line(0).unwrap().len(), 3);
+        assert_eq!(table.get_line(1).unwrap().len(), 3);
+        assert_eq!(table.get_line(2).unwrap().len(), 3);
+        assert_eq!(table.get_cell((0, 0)).unwrap().to_string(), "1");
+        assert_eq!(table.get_cell((0, 1)).unwrap().to_string(), "2223");
+        assert_eq!(table.get_cell((0, 2)).unwrap().to_string(), "3");
+        assert_eq!(table.get_cell((1, 0)).unwrap().to_string(), "4");
+        assert_eq!(table.get_cell((1, 1)).unwrap().to_string(), "5");
+        assert_eq!(table.get_cell((1, 2)).unwrap().to_string(), "6");
+        assert_eq!(table.get_cell((2, 0)).unwrap().to_string(), "7");
+        assert_eq!(table.get_cell((2, 1)).unwrap().to_string(), "8");
+        assert_eq!(table.get_cell((2, 2)).unwrap().to_string(), "9");
+    }
+
+    #[test]
+    fn test_validate_no_duplicate_keys() {
+        let s = "1,a\n2,b\n3,a\n4,c\n5,a".to_string();
+        let table = Table::from_string(s, ",", "\n");
+        assert!(table.validate_no_duplicate_keys(0).is_ok());
+        let error = table.validate_no_duplicate_keys(1).unwrap_err();
+        assert_eq!(error.column, 2);
+        assert_eq!(error.duplicates, vec![("a".to_string(), vec![1, 3, 5])]);
+        assert_eq!(
+            error.reason().unwrap(),
+            "Column 2 has duplicate values:\n\t\"a\" in lines [1, 3, 5]"
+        );
+        // column out of range has nothing to compare
+        assert!(table.validate_no_duplicate_keys(5).is_ok());
+
+        // the int `1` and the string `1` are not duplicates
+        let table = Table::from_vec(vec![
+            Tableline::from_vec(vec![Tablecell::int(1)]),
+            Tableline::from_vec(vec![Tablecell::string("1")]),
+            Tableline::from_vec(vec![Tablecell::int(1)]),
+        ]);
+        let error = table.validate_no_duplicate_keys(0).unwrap_err();
+        assert_eq!(error.duplicates, vec![("1".to_string(), vec![1, 3])]);
+    }
+
+    #[test]
+    fn test_retain_rows() {
+        let s = "1,a\nb,2\n3,c\nd,4\n5,e".to_string();
+        let mut table = Table::from_string(s, ",", "\n");
+        table.retain_rows(|line| {
+            !matches!(
+                line.get_cell(0).map(|cell| &cell.core),
+                Some(Tablecellcore::String(_))
+            )
+        });
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.get_cell((0, 0)).unwrap().to_string(), "1");
+        assert_eq!(table.get_cell((1, 0)).unwrap().to_string(), "3");
+        assert_eq!(table.get_cell((2, 0)).unwrap().to_string(), "5");
+    }
+
+    #[test]
+    fn test_validate_rectangular() {
+        let table = Table::from_string("a 1\nb 2\nc 3".to_string(), " ", "\n");
+        assert!(table.validate_rectangular().is_ok());
+        assert!(Table::new().validate_rectangular().is_ok());
+
+        let table = Table::from_string("a 1\nb 2 x\nc".to_string(), " ", "\n");
+        let err = table.validate_rectangular().unwrap_err();
+        assert_eq!((err.line, err.count, err.expected), (2, 3, 2));
+        assert_eq!(
+            err.reason().unwrap(),
+            "Line 2 has 3 cells, but the first line has 2."
+        );
+    }
+
+    #[test]
+    fn test_pad_to_rectangular() {
+        let mut table = Table::from_string("a 1 x\nb".to_string(), " ", "\n");
+        table.pad_to_rectangular(Tablecell::force_as_string("-".to_string()));
+        assert_eq!(table.dimensions(), (2, 3));
+        assert_eq!(table.get_line(1).unwrap().len(), 3);
+        assert_eq!(table[(1, 1)].to_string(), "-");
+        assert_eq!(table[(1, 2)].to_string(), "-");
+        assert_eq!(table[(0, 2)].to_string(), "x");
+    }
+
+    #[test]
+    fn test_empty_placeholder() {
+        let mut table = Table::from_string("a 1 x\nbb\nc 3".to_string(), " ", "\n");
+        table.set_empty_placeholder(Some("NA".to_string()));
+
+        let mut buffer: Vec<u8> = Vec::new();
+        table.write_csv(&mut buffer).unwrap();
+        assert_eq!(buffer, b"a,1,x\nbb,NA,NA\nc,3,NA\n");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        table.write_txt(&mut buffer, ',').unwrap();
+        assert_eq!(buffer, b"a, 1, x, \nbb, NA, NA, \nc, 3, NA, \n");
+
+        table.set_color_choice(ColorChoice::Never);
+        let s = table.to_string();
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(lines[0], "+----+----+----+");
+        assert_eq!(lines[3], "| bb | NA | NA | ");
+        assert_eq!(lines[5], "| c  | 3  | NA | ");
+
+        // the table itself is not changed
+        assert_eq!(table.get_line(1).unwrap().len(), 1);
+        table.set_empty_placeholder(None);
+        let mut buffer: Vec<u8> = Vec::new();
+        table.write_csv(&mut buffer).unwrap();
+        assert_eq!(buffer, b"a,1,x\nbb,,\nc,3,\n");
+    }
+
+    #[test]
+    fn test_write_to_vec() {
+        let s = "a 1\nb,c 2.5 x".to_string();
+        let table = Table::from_string(s, " ", "\n");
+        let mut buffer: Vec<u8> = Vec::new();
+        table.write_txt(&mut buffer, ';').unwrap();
+        assert_eq!(buffer, b"a; 1; \nb,c; 2.5; x; \n");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        table.write_csv(&mut buffer).unwrap();
+        assert_eq!(buffer, b"a,1,\n\"b,c\",2.5,x\n");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        table.write_tsv(&mut buffer).unwrap();
+        assert_eq!(buffer, b"a\t1\nb,c\t2.5\tx\n");
+
+        let table = Table::from_string("a\tb c".to_string(), " ", "\n");
+        let mut buffer: Vec<u8> = Vec::new();
+        assert!(table.write_tsv(&mut buffer).is_err());
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_append_to_file() {
+        let dir = std::env::temp_dir();
+        let txt = dir.join("str2table_test_append.txt");
+        let csv = dir.join("str2table_test_append.csv");
+        let (txt, csv) = (txt.to_str().unwrap(), csv.to_str().unwrap());
+        let _ = std::fs::remove_file(txt);
+        let _ = std::fs::remove_file(csv);
+
+        let first = Table::from_string("a 1\nb 2".to_string(), " ", "\n");
+        let second = Table::from_string("c,d 3".to_string(), " ", "\n");
+        first.append_txt(txt, ';').unwrap();
+        second.append_txt(txt, ';').unwrap();
+        assert_eq!(
+            std::fs::read_to_string(txt).unwrap(),
+            "a; 1; \nb; 2; \nc,d; 3; \n"
+        );
+        first.append_csv(csv).unwrap();
+        second.append_csv(csv).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(csv).unwrap(),
+            "a,1\nb,2\n\"c,d\",3\n"
+        );
+    }
+
+    #[test]
+    fn test_tsv_round_trip() {
+        let s = "name\tvalue\tnote\r\nx\t1\t\n\ny, z\t2.5\ttrue\n".to_string();
+        let table = Table::from_tsv(s);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.get_line(1).unwrap().len(), 3);
+        assert_eq!(table.get_cell((1, 2)).unwrap().to_string(), "");
+        assert_eq!(table.get_cell((2, 0)).unwrap().to_string(), "y, z");
+        assert_eq!(
+            table.get_cell((2, 1)).unwrap().core,
+            Tablecellcore::Float(2.5)
+        );
+
+        let file = std::env::temp_dir().join("str2table_test_round_trip.tsv");
+        table.to_tsv(file.to_str().unwrap()).unwrap();
+        let s = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(s, "name\tvalue\tnote\nx\t1\t\ny, z\t2.5\ttrue\n");
+        assert!(Table::from_tsv(s) == table);
+
+        // a tab or line break in a cell can not be written
+        let table = Table::from_string("a\tb,c".to_string(), ",", "\n");
+        let err = table.to_tsv(file.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_to_csv_with_row_numbers() {
+        let s = "a,b\nc,\"d\"\ne,f".to_string();
+        let mut table = Table::from_string(s, ",", "\n");
+        table.prepend_row_numbers(false);
+        let file = std::env::temp_dir().join("str2table_test_row_numbers.csv");
+        table.to_csv(file.to_str().unwrap()).unwrap();
+        let s = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(s, "1,a,b\n2,c,\"\"\"d\"\"\"\n3,e,f\n");
+        for (line_num, line) in s.lines().enumerate() {
+            assert_eq!(line.split(',').next().unwrap(), (line_num + 1).to_string());
+        }
+
+        // header gets `#` and is not counted
+        let s = "name,value\nx,1\ny,2".to_string();
+        let mut table = Table::from_string(s, ",", "\n");
+        table.merge_cells_display(0, 0, 1).unwrap();
+        table.prepend_row_numbers(true);
+        table.to_csv(file.to_str().unwrap()).unwrap();
+        let s = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(s, "#,name,value\n1,x,1\n2,y,2\n");
+        // the merged header moves with its cells
+        assert_eq!(table.spans(), &[(0, 1, 2)]);
+    }
+
+    #[test]
+    fn test_get_subtable() {
+        let raw = |table: &Table| -> Vec<String> {
+            (0..table.len())
+                .map(|i| table.get_line(i).unwrap().to_string_raw(','))
+                .collect()
+        };
+        let s = "1,2,3\n4,5,6\n7,8,9".to_string();
+        let table = Table::from_string(s, ",", "\n");
+        let subtable = table.get_subtable((vec![2, 0, 2], vec![1, 0, 1])).unwrap();
+        assert_eq!(raw(&subtable), vec!["1, 2, ", "7, 8, "]);
+
+        // the table is still usable after extracting a subtable
+        assert_eq!(table.len(), 3);
+        assert_eq!(raw(&table), vec!["1, 2, 3, ", "4, 5, 6, ", "7, 8, 9, "]);
+        let subtable = table
+            .get_subtable_ordered((vec![2, 0, 2], vec![1, 0, 1]))
+            .unwrap();
+        assert_eq!(raw(&subtable), vec!["8, 7, 8, ", "2, 1, 2, ", "8, 7, 8, "]);
+    }
+
+    #[test]
+    fn test_subtable_keeps_color() {
+        let mut table = Table::from_string("1 2 3\n4 5 6\n7 8 9".to_string(), " ", "\n");
+        table.set_color_line(0, OutputColor::Red);
+        table.set_color_column(2, OutputColor::Green);
+        table.lines[2].0[0].set_background(OutputColor::Blue);
+
+        let colors = |table: &Table| -> Vec<String> {
+            (0..table.len())
+                .map(|i| format!("{:?}", table.get_line(i).unwrap()))
+                .collect()
+        };
+        let subtable = table.get_subtable((vec![0, 2], vec![0, 2])).unwrap();
+        assert_eq!(
+            colors(&subtable),
+            [
+                "| 1<int><Red> | 3<int><Green> | ",
+                "| 7<int><Black> | 9<int><Green> | "
+            ]
+        );
+        assert_eq!(subtable[(1, 0)].background, Some(OutputColor::Blue));
+
+        let subtable = table
+            .get_subtable_ordered((vec![2, 0], vec![2, 1]))
+            .unwrap();
+        assert_eq!(
+            colors(&subtable),
+            [
+                "| 9<int><Green> | 8<int><Black> | ",
+                "| 3<int><Green> | 2<int><Red> | "
+            ]
+        );
+
+        // colored after, by the indices of the subtable
+        let mut subtable = table.apply_subtable_spec((vec![2, 3], vec![])).unwrap();
+        subtable.set_color_line(0, OutputColor::Yellow);
+        assert_eq!(
+            colors(&subtable),
+            [
+                "| 4<int><Yellow> | 5<int><Yellow> | 6<int><Yellow> | ",
+                "| 7<int><Black> | 8<int><Black> | 9<int><Green> | "
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_by_column() {
+        let s = "a 5\nb 5.0\nc 6\nd\ne 05\nf 5 x".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        table.set_color_line(4, OutputColor::Red);
+        let filtered = table.filter_by_column(1, &Tablecellcore::Int(IBig::from(5)));
+        assert_eq!(
+            (0..filtered.len())
+                .map(|i| format!("{:?}", filtered.get_line(i).unwrap()))
+                .collect::<Vec<String>>(),
+            [
+                "| a<str><Black> | 5<int><Black> | ",
+                "| e<str><Red> | 5<int><Red> | ",
+                "| f<str><Black> | 5<int><Black> | x<str><Black> | "
+            ]
+        );
+        assert_eq!(table.len(), 6);
+        assert!(table
+            .filter_by_column(3, &Tablecellcore::Int(IBig::from(5)))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_group_count() {
+        let s = "x apple\ny pear\nz apple\nw\nv fig\nu pear\nt apple".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        table.set_color_line(0, OutputColor::Red);
+        let counts = table.group_count(1);
+        assert_eq!(
+            (0..counts.len())
+                .map(|i| format!("{:?}", counts.get_line(i).unwrap()))
+                .collect::<Vec<String>>(),
+            [
+                "| apple<str><Black> | 3<int><Black> | ",
+                "| pear<str><Black> | 2<int><Black> | ",
+                "| fig<str><Black> | 1<int><Black> | "
+            ]
+        );
+        assert!(table.group_count(2).is_empty());
+
+        // the same text of different types are different values
+        let mut table = Table::from_string("a 5\nb 5\nc 5".to_string(), " ", "\n");
+        table.set_cell((1, 1), Tablecell::string("5")).unwrap();
+        let counts = table.group_count(1);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(
+            format!("{:?}", counts.get_line(0).unwrap()),
+            "| 5<int><Black> | 2<int><Black> | "
+        );
+        assert_eq!(
+            format!("{:?}", counts.get_line(1).unwrap()),
+            "| 5<str><Black> | 1<int><Black> | "
+        );
+    }
+
+    #[test]
+    fn test_pivot() {
+        let s =
+            "2023 apple 3\n2023 pear 5\n2024 apple 4\n2025 fig 1.5\n2024\n2023 apple 6".to_string();
+        let table = Table::from_string(s, " ", "\n");
+        let wide = table.pivot(0, 1, 2);
+        assert_eq!(
+            (0..wide.len())
+                .map(|i| format!("{:?}", wide.get_line(i).unwrap()))
+                .collect::<Vec<String>>(),
+            [
+                "| <empty><Black> | apple<str><Black> | pear<str><Black> | fig<str><Black> | ",
+                "| 2023<int><Black> | 6<int><Black> | 5<int><Black> | <empty><Black> | ",
+                "| 2024<int><Black> | 4<int><Black> | <empty><Black> | <empty><Black> | ",
+                "| 2025<int><Black> | <empty><Black> | <empty><Black> | 1.5<float><Black> | "
+            ]
+        );
+
+        // no line has the value column
+        let wide = table.pivot(0, 1, 3);
+        assert_eq!(wide.len(), 1);
+        assert_eq!(wide.get_line(0).unwrap().len(), 1);
+
+        // keys of different types are not merged
+        let table = Table::from_vec(vec![
+            Tableline::from_vec(vec![
+                Tablecell::int(1),
+                Tablecell::string("a"),
+                Tablecell::int(2),
+            ]),
+            Tableline::from_vec(vec![
+                Tablecell::string("1"),
+                Tablecell::string("a"),
+                Tablecell::int(3),
+            ]),
+        ]);
+        let wide = table.pivot(0, 1, 2);
+        assert_eq!(
+            (1..wide.len())
+                .map(|i| format!("{:?}", wide.get_line(i).unwrap()))
+                .collect::<Vec<String>>(),
+            [
+                "| 1<int><Black> | 2<int><Black> | ",
+                "| 1<str><Black> | 3<int><Black> | "
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clone() {
+        let table = Table::from_string("a 1\nb 2".to_string(), " ", "\n");
+        let mut cloned = table.clone();
+        assert!(cloned == table);
+        cloned[(0, 0)] = Tablecell::auto_from("x".to_string());
+        cloned.push_line(Tableline::from_string("c 3".to_string(), " "));
+        assert_eq!(table.len(), 2);
+        assert_eq!(table[(0, 0)].core.to_string(), "a");
+        assert_eq!(cloned.len(), 3);
+        assert_eq!(cloned[(0, 0)].core.to_string(), "x");
+
+        // the ordered subtable does not consume the table
+        let subtable = table.get_subtable_ordered((vec![1], vec![1, 0])).unwrap();
+        assert_eq!(subtable.get_line(0).unwrap().to_string_raw(','), "2, b, ");
+        let subtable = table.apply_subtable_spec((vec![1], vec![])).unwrap();
+        assert_eq!(subtable.get_line(0).unwrap().to_string_raw(','), "a, 1, ");
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn test_column_aggregate() {
+        let s = "name 3 1.5\nb 4 x\nc -2 2\nd 10000000000000000000000 y".to_string();
+        let table = Table::from_string(s, " ", "\n");
+
+        // ints stay ints
+        let big = IBig::from(10u8).pow(22);
+        assert_eq!(
+            table.column_sum(1),
+            Some(Tablecellcore::Int(big.clone() + 5))
+        );
+        assert_eq!(
+            table.column_min(1),
+            Some(Tablecellcore::Int(IBig::from(-2)))
+        );
+        assert_eq!(table.column_max(1), Some(Tablecellcore::Int(big)));
+        assert_eq!(table.column_mean(1), Some(Tablecellcore::Float(2.5e21)));
+
+        // strings are ignored and ints with floats make a float
+        assert_eq!(table.column_sum(2), Some(Tablecellcore::Float(3.5)));
+        assert_eq!(table.column_mean(2), Some(Tablecellcore::Float(1.75)));
+        assert_eq!(table.column_min(2), Some(Tablecellcore::Float(1.5)));
+        assert_eq!(table.column_max(2), Some(Tablecellcore::Int(IBig::from(2))));
+
+        // no number at all
+        assert_eq!(table.column_sum(0), None);
+        assert_eq!(table.column_mean(0), None);
+        assert_eq!(table.column_min(0), None);
+        assert_eq!(table.column_max(0), None);
+        assert_eq!(table.column_sum(5), None);
+    }
+
+    #[test]
+    fn test_with_summary_row() {
+        let s = "a 1 2.5 x\nb 3 1.5\nc 5 x".to_string();
+        let table = Table::from_string(s, " ", "\n");
+        let mut summary = table.with_summary_row(&[
+            SummaryOp::None,
+            SummaryOp::Sum,
+            SummaryOp::Mean,
+            SummaryOp::Count,
+        ]);
+        assert_eq!(table.len(), 3);
+        assert_eq!(summary.len(), 4);
+        assert_eq!(summary[(3, 0)].to_string(), "");
+        assert_eq!(summary[(3, 1)].core, Tablecellcore::Int(IBig::from(9)));
+        assert_eq!(summary[(3, 2)].core, Tablecellcore::Float(2.0));
+        // the last column has only strings
+        assert_eq!(summary[(3, 3)].core, Tablecellcore::Int(IBig::from(0)));
+
+        // missing ops are empty cells
+        let short = table.with_summary_row(&[SummaryOp::Count]);
+        assert_eq!(short.get_line(3).unwrap().len(), 4);
+        assert_eq!(short[(3, 0)].to_string(), "0");
+        assert_eq!(short[(3, 1)].to_string(), "");
+
+        summary.set_color_line(3, OutputColor::Yellow);
+        assert_eq!(summary[(3, 1)].color, OutputColor::Yellow);
+        assert_eq!(summary[(2, 1)].color, OutputColor::default());
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let mut table: Table = ["a 1", "b 2", "c 3"]
+            .iter()
+            .map(|line| Tableline::from_string(line.to_string(), " "))
+            .collect();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table[(2, 0)].to_string(), "c");
+        table.extend(vec![Tableline::from_string("d 4".to_string(), " ")]);
+        assert_eq!(table.len(), 4);
+        assert_eq!(table[(3, 1)].core, Tablecellcore::Int(IBig::from(4)));
+    }
+
+    #[test]
+    fn test_index() {
+        let mut table = Table::from_string("a b\nc".to_string(), " ", "\n");
+        assert_eq!(table[(1, 0)].to_string(), "c");
+        table[(0, 1)].set_color(OutputColor::Green);
+        assert_eq!(table[(0, 1)].color, OutputColor::Green);
+        assert_eq!(table.get_cell((0, 1)).unwrap().color, OutputColor::Green);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_range() {
+        let table = Table::from_string("a b\nc".to_string(), " ", "\n");
+        let _ = &table[(1, 1)];
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let input: &[u8] = b"a 1\r\n# comment\n\nb 2\n";
+        let mut args = InputArgs::default();
+        args.parse_options.comment_prefix = Some("#".to_string());
+        let table = Table::from_reader(std::io::Cursor::new(input), " ", "\n", &args).unwrap();
+        assert_eq!(table.dimensions(), (2, 2));
+        assert_eq!(table[(1, 0)].core.to_string(), "b");
+
+        args.parse_options.regex_seperation = true;
+        let err = Table::from_reader(std::io::Cursor::new(input), "(", "\n", &args).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(err.into_inner().unwrap().downcast::<ArgError>().is_ok());
+
+        let invalid: &[u8] = b"a \xff";
+        let err = Table::from_reader(invalid, " ", "\n", &InputArgs::default()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_truncate_warnings() {
+        let mut args = InputArgs::default();
+        args.parse_options.cell_max_bytes = Some(3);
+        let input: &[u8] = b"abcdef 1\nxy 123456";
+        let (table, warnings) = Table::from_reader_with_warnings(input, " ", "\n", &args).unwrap();
+        assert_eq!(table[(0, 0)].core.to_string(), "abc");
+        assert_eq!(table[(1, 1)].core.to_string(), "123");
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[1].error_arg.as_deref(), Some("cell of 6 bytes"));
+
+        let (table, warnings) = Table::from_csv_with_warnings("abcdef,1\n", &args).unwrap();
+        assert_eq!(table[(0, 0)].core.to_string(), "abc");
+        assert_eq!(warnings.len(), 1);
+
+        // forced lines and the lines not in the rule, i.e. `-f 1ls`
+        args.force_parse = Some((vec![(1, setting::ForceType::S)], setting::LineColumn::Line));
+        let input: &[u8] = b"abcdef 1\nxy 123456";
+        let (table, warnings) = Table::from_reader_with_warnings(input, " ", "\n", &args).unwrap();
+        assert_eq!(table[(0, 0)].core, Tablecellcore::String("abc".to_string()));
+        assert_eq!(table[(1, 1)].core.to_string(), "123");
+        assert_eq!(warnings.len(), 2);
+
+        // parse mode S
+        args.force_parse = None;
+        args.parse_mode = setting::ParseMode::S;
+        let (table, warnings) = Table::from_reader_with_warnings(input, " ", "\n", &args).unwrap();
+        assert_eq!(table[(1, 1)].core, Tablecellcore::String("123".to_string()));
+        assert_eq!(warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_mode_null() {
+        let args = InputArgs {
+            parse_mode: setting::ParseMode::N,
+            ..InputArgs::default()
+        };
+        let input: &[u8] = b"a,,b\n1,2,";
+        let table = Table::from_reader(input, ",", "\n", &args).unwrap();
+        assert_eq!(table.dimensions(), (2, 3));
+        assert_eq!(table[(0, 1)].core, Tablecellcore::Empty);
+        assert_eq!(table[(0, 2)].core, Tablecellcore::String("b".to_string()));
+        assert_eq!(table[(1, 0)].core, Tablecellcore::String("1".to_string()));
+        assert_eq!(table[(1, 2)].core, Tablecellcore::Empty);
+    }
+
+    #[test]
+    fn test_export_empty() {
+        let options = ParseOptions {
+            collapse_empty: false,
+            empty_as_null: true,
+            ..ParseOptions::default()
+        };
+        let mut table = Table::from_string_with_options("a,,1".to_string(), ",", "\n", &options);
+        assert_eq!(table[(0, 1)].core, Tablecellcore::Empty);
+
+        table.set_color_choice(ColorChoice::Never);
+        assert_eq!(
+            table.to_string(),
+            "+---+--+---+\n| a |  | 1 | \n+---+--+---+\n"
+        );
+
+        let mut buffer: Vec<u8> = Vec::new();
+        table.write_csv(&mut buffer).unwrap();
+        assert_eq!(buffer, b"a,,1\n");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        table.write_tsv(&mut buffer).unwrap();
+        assert_eq!(buffer, b"a\t\t1\n");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        table.write_txt(&mut buffer, ',').unwrap();
+        assert_eq!(buffer, b"a, , 1, \n");
+
+        assert_eq!(
+            table.debug_dump().lines().nth(1).unwrap(),
+            r#"{"row":0,"col":1,"raw":"","type":"empty","value":null}"#
+        );
+        assert!(table
+            .to_html_string()
+            .contains("<td class=\"c-black\"></td>"));
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let table = Table::new();
+        assert!(table.is_empty());
+        assert_eq!(table.dimensions(), (0, 0));
+
+        let table = Table::from_string("1 2\n3 4 5 6\n7".to_string(), " ", "\n");
+        assert!(!table.is_empty());
+        assert_eq!(table.dimensions(), (3, 4));
+    }
+
+    #[test]
+    fn test_last_line() {
+        let mut table = Table::from_string("a 1\nb 2\nc 3 x".to_string(), " ", "\n");
+        let last = table.last_line().unwrap();
+        assert_eq!(last.to_string_raw(','), "c, 3, x, ");
+        assert_eq!(last.last_cell().unwrap().core.to_string(), "x");
+        assert_eq!(table.line_from_end(0), table.last_line());
+        assert_eq!(table.line_from_end(2), table.get_line(0));
+        assert!(table.line_from_end(3).is_none());
+
+        table
+            .last_line_mut()
+            .unwrap()
+            .push_cell(Tablecell::auto_from("y".to_string()));
+        assert_eq!(
+            table.last_line().unwrap().to_string_raw(','),
+            "c, 3, x, y, "
+        );
+
+        let mut table = Table::new();
+        assert!(table.last_line().is_none());
+        assert!(table.last_line_mut().is_none());
+        assert!(table.line_from_end(0).is_none());
+    }
+
+    #[test]
+    fn test_get_lines_clamped() {
+        let table = Table::from_string("1\n2\n3".to_string(), " ", "\n");
+        assert!(table.get_lines(1, 5).is_none());
+        assert_eq!(table.get_lines_clamped(1, 5), &table.lines[1..]);
+        assert_eq!(table.get_lines_clamped(0, 0), &table.lines[..1]);
+        assert!(table.get_lines_clamped(3, 5).is_empty());
+        assert!(table.get_lines_clamped(2, 1).is_empty());
+        assert!(Table::new().get_lines_clamped(0, 0).is_empty());
+
+        assert_eq!(table.get_lines_from(1), &table.lines[1..]);
+        assert!(table.get_lines_from(3).is_empty());
+        assert!(table.get_lines_from(7).is_empty());
+    }
+
+    #[test]
+    fn test_force_parse_first_line() {
+        let s = "1 2\n3 4".to_string();
+        let args = InputArgs {
+            force_parse: Some((vec![(1, setting::ForceType::S)], setting::LineColumn::Line)),
+            ..InputArgs::default()
+        };
+        let table = Table::from_string_with_force_parse(s, " ", "\n", &args);
+        assert_eq!(
+            table.get_cell((0, 0)).unwrap().core,
+            Tablecellcore::String("1".to_string())
+        );
+        assert_eq!(
+            table.get_cell((1, 0)).unwrap().core,
+            Tablecellcore::Int(IBig::from(3))
+        );
+    }
+
+    #[test]
+    fn test_force_parse_line_with_options() {
+        // the parse options apply to the forced lines and the others
+        let args = InputArgs {
+            force_parse: Some((vec![(1, setting::ForceType::S)], setting::LineColumn::Line)),
+            parse_options: ParseOptions {
+                quoted: true,
+                rational: true,
+                collapse_empty: false,
+                ..ParseOptions::default()
+            },
+            ..InputArgs::default()
+        };
+        let s = "\"a b\" 7\n6/8  \"c d\"".to_string();
+        let table = Table::from_string_with_force_parse(s, " ", "\n", &args);
+        assert_eq!(
+            format!("{:?}", table.get_line(0).unwrap()),
+            "| a b<str><Black> | 7<str><Black> | "
+        );
+        assert_eq!(
+            format!("{:?}", table.get_line(1).unwrap()),
+            "| 3/4<rational><Black> | <str><Black> | c d<str><Black> | "
+        );
+    }
+
+    #[test]
+    fn test_force_parse_column() {
+        let s = "a 0x10 7\n# comment\nb  zz 8\nc 3 9".to_string();
+        let mut args = InputArgs::default();
+        args.parse_options.comment_prefix = Some("#".to_string());
+
+        // the 2nd column to int, falling back to auto when it is not an int
+        args.force_parse = Some((
+            vec![(2, setting::ForceType::I)],
+            setting::LineColumn::Column,
+        ));
+        let table = Table::from_string_with_force_parse(s.clone(), " ", "\n", &args);
+        assert_eq!(table.len(), 3);
+        assert_eq!(
+            table.get_cell((0, 1)).unwrap().core,
+            Tablecellcore::Int(IBig::from(16))
+        );
+        assert_eq!(
+            table.get_cell((1, 1)).unwrap().core,
+            Tablecellcore::String("zz".to_string())
+        );
+        assert_eq!(
+            table.get_cell((2, 2)).unwrap().core,
+            Tablecellcore::Int(IBig::from(9))
+        );
+
+        // the 3rd column to string
+        args.force_parse = Some((
+            vec![(3, setting::ForceType::S)],
+            setting::LineColumn::Column,
+        ));
+        let table = Table::from_string_with_force_parse(s.clone(), " ", "\n", &args);
+        for row in 0..3 {
+            assert!(matches!(
+                table.get_cell((row, 2)).unwrap().core,
+                Tablecellcore::String(_)
+            ));
         }
-        write!(f, "{}", s)
+
+        // lines are 1-based and comment lines are not counted
+        args.force_parse = Some((vec![(2, setting::ForceType::S)], setting::LineColumn::Line));
+        let table = Table::from_string_with_force_parse(s, " ", "\n", &args);
+        assert_eq!(
+            table.get_cell((1, 2)).unwrap().core,
+            Tablecellcore::String("8".to_string())
+        );
+        assert_eq!(
+            table.get_cell((2, 2)).unwrap().core,
+            Tablecellcore::Int(IBig::from(9))
+        );
     }
-}
 
-/* ---------------------------------- tests --------------------------------- */
+    #[test]
+    fn test_force_parse_warnings() {
+        let s = "a 1 x\nb zz y\nc 3 z\nd 4.5 w".to_string();
+        let mut args = InputArgs {
+            force_parse: Some((
+                vec![(2, setting::ForceType::I), (3, setting::ForceType::S)],
+                setting::LineColumn::Column,
+            )),
+            ..InputArgs::default()
+        };
+        let (table, warnings) =
+            Table::from_string_with_force_parse_with_warnings(s.clone(), " ", "\n", &args);
+        assert_eq!(
+            table.get_cell((1, 1)).unwrap().core,
+            Tablecellcore::String("zz".to_string())
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].level(), ErrorLevel::Warning);
+        assert_eq!(
+            warnings[0].reason().unwrap(),
+            "Error happens in \"--force-parse\", where \"column 2\" couldn't be forced to int \
+             for 2 cell(s), e.g. \"zz\", used auto"
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // a forced line, the warnings are sorted by the number of the line
+        args.force_parse = Some((
+            vec![(4, setting::ForceType::B), (2, setting::ForceType::S)],
+            setting::LineColumn::Line,
+        ));
+        let (_table, warnings) =
+            Table::from_string_with_force_parse_with_warnings(s.clone(), " ", "\n", &args);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].error_arg.as_deref(), Some("line 4"));
+
+        // the warnings are dropped by from_reader() but kept by from_reader_with_warnings()
+        let (_table, warnings) =
+            Table::from_reader_with_warnings(std::io::Cursor::new(s), " ", "\n", &args).unwrap();
+        assert_eq!(warnings.len(), 1);
+
+        // empty cells have nothing to force
+        let args = InputArgs {
+            force_parse: Some((
+                vec![(2, setting::ForceType::I)],
+                setting::LineColumn::Column,
+            )),
+            parse_options: ParseOptions {
+                collapse_empty: false,
+                ..ParseOptions::default()
+            },
+            ..InputArgs::default()
+        };
+        let (_table, warnings) = Table::from_string_with_force_parse_with_warnings(
+            "a,,x\nb,2,y".to_string(),
+            ",",
+            "\n",
+            &args,
+        );
+        assert!(warnings.is_empty());
+    }
 
     #[test]
-    fn test_from_string_simple() {
-        let s = "1,2223,3\n4,5,6\n7,8,9".to_string();
-        let table = Table::from_string(s, ",", "\n");
-        println!("{:?}", table);
+    fn test_force_parse_column_keep_empty_fields() {
+        // `-f 2ci --keep-empty-fields`, the trailing line break is not an empty line
+        let args = InputArgs {
+            force_parse: Some((
+                vec![(2, setting::ForceType::I)],
+                setting::LineColumn::Column,
+            )),
+            parse_options: ParseOptions {
+                collapse_empty: false,
+                ..ParseOptions::default()
+            },
+            ..InputArgs::default()
+        };
+        let table =
+            Table::from_string_with_force_parse("a,1,\n  \nb,2,x\n".to_string(), ",", "\n", &args);
+        assert_eq!(table.dimensions(), (2, 3));
+        assert_eq!(table[(0, 2)].core, Tablecellcore::String(String::new()));
+        assert_eq!(table[(1, 1)].core, Tablecellcore::Int(IBig::from(2)));
+    }
+
+    #[test]
+    fn test_custom_end_line_with_crlf() {
+        let s = "a,1;\r\nb,2;\r\nc,3\r\n".to_string();
+        let table = Table::from_string(s.clone(), ",", ";");
         assert_eq!(table.len(), 3);
+        for i in 0..table.len() {
+            let line = table.get_line(i).unwrap().to_string_raw(',');
+            assert!(!line.contains('\r'));
+        }
+        assert_eq!(table.get_line(2).unwrap().to_string_raw(','), "c, 3, ");
+        let table = Table::from_string_force(s, ",", ";");
+        assert!(!format!("{:?}", table).contains('\r'));
+    }
+
+    #[test]
+    fn test_display_float_format() {
+        let mut table = Table::from_string("1.5e-20 3.14159\n2 x".to_string(), " ", "\n");
+        table.set_float_format(FloatFormat::Scientific);
+        let s = table.to_string();
+        assert!(s.contains("1.5e-20"));
+        assert!(s.contains("3.14159e0"));
+        table.set_float_format(FloatFormat::Precision(2));
+        let s = table.to_string();
+        assert!(s.contains(" 0.00 "));
+        assert!(s.contains(" 3.14 "));
+        assert!(!s.contains("3.14159"));
+        // the widths follow the shown text
+        assert!(s.starts_with("\x1b[90m+------\x1b[90m+------+"));
+    }
+
+    #[test]
+    fn test_max_column_width() {
+        let s = format!("{} b\nshort c", "a".repeat(100));
+        let mut table = Table::from_string(s, " ", "\n");
+        table.set_max_column_width(10);
+        let s = table.to_string();
+        assert!(s.contains(&format!(" {}… ", "a".repeat(9))));
+        assert!(!s.contains(&"a".repeat(10)));
+        assert!(s.contains(" short      "));
+        assert!(s.starts_with(&format!("\x1b[90m+{}\x1b[90m+---+", "-".repeat(12))));
+        // the cell keeps its value
+        assert_eq!(table.get_cell((0, 0)).unwrap().len(), 100);
+    }
+
+    #[test]
+    fn test_display_only_table_lines() {
+        let table = Table::from_string("12 abc\n3.5 true".to_string(), " ", "\n");
+        assert_eq!(table.get_cell((0, 0)).unwrap().len(), 2);
+        assert_eq!(table.get_cell((1, 1)).unwrap().len(), 4);
+        let s = table.to_string();
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(lines.len(), 5);
+        for line in lines {
+            assert!(line.starts_with("\x1b[90m+") || line.starts_with("\x1b[90m|"));
+        }
+    }
+
+    #[test]
+    fn test_display_wide_chars() {
+        use unicode_width::UnicodeWidthStr;
+        let table = Table::from_string("ab 测试 x\n😀 c 测".to_string(), " ", "\n");
+        assert_eq!(table.get_cell((0, 1)).unwrap().len(), 4);
+        assert_eq!(table.get_cell((1, 0)).unwrap().len(), 2);
+        let ansi = Regex::new("\x1b\\[[0-9;]*m").unwrap();
+        let s = table.to_string();
+        let lines: Vec<String> = s
+            .lines()
+            .map(|line| ansi.replace_all(line, "").trim_end().to_string())
+            .collect();
+        assert_eq!(lines[0], "+----+------+----+");
+        assert_eq!(lines[1], "| ab | 测试 | x  |");
+        assert_eq!(lines[3], "| 😀 | c    | 测 |");
+        for line in lines.iter() {
+            assert_eq!(line.as_str().width(), lines[0].as_str().width());
+        }
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let args = InputArgs::default();
+        let table =
+            Table::from_csv("a,\"b,c\",1\r\n\"x\ny\",\"say \"\"hi\"\"\",2.5\n", &args).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.get_cell((0, 1)).unwrap().to_string(), "b,c");
+        assert_eq!(
+            table.get_cell((0, 2)).unwrap().core,
+            Tablecellcore::Int(IBig::from(1))
+        );
+        assert_eq!(table.get_cell((1, 0)).unwrap().to_string(), "x\ny");
+        assert_eq!(table.get_cell((1, 1)).unwrap().to_string(), "say \"hi\"");
+        assert_eq!(
+            table.get_cell((1, 2)).unwrap().core,
+            Tablecellcore::Float(2.5)
+        );
+
+        // empty fields are kept and rows can have different lengths
+        let table = Table::from_csv("1,,3\n4\n", &args).unwrap();
         assert_eq!(table.get_line(0).unwrap().len(), 3);
-        assert_eq!(table.get_line(1).unwrap().len(), 3);
-        assert_eq!(table.get_line(2).unwrap().len(), 3);
-        assert_eq!(table.get_cell((0, 0)).unwrap().to_string(), "1");
-        assert_eq!(table.get_cell((0, 1)).unwrap().to_string(), "2223");
-        assert_eq!(table.get_cell((0, 2)).unwrap().to_string(), "3");
-        assert_eq!(table.get_cell((1, 0)).unwrap().to_string(), "4");
-        assert_eq!(table.get_cell((1, 1)).unwrap().to_string(), "5");
-        assert_eq!(table.get_cell((1, 2)).unwrap().to_string(), "6");
-        assert_eq!(table.get_cell((2, 0)).unwrap().to_string(), "7");
-        assert_eq!(table.get_cell((2, 1)).unwrap().to_string(), "8");
-        assert_eq!(table.get_cell((2, 2)).unwrap().to_string(), "9");
+        assert_eq!(table.get_line(1).unwrap().len(), 1);
+
+        // force parse still applies
+        let args = InputArgs {
+            force_parse: Some((
+                vec![(1, setting::ForceType::S)],
+                setting::LineColumn::Column,
+            )),
+            ..InputArgs::default()
+        };
+        let table = Table::from_csv("1,2\n", &args).unwrap();
+        assert_eq!(
+            table.get_cell((0, 0)).unwrap().core,
+            Tablecellcore::String("1".to_string())
+        );
+        assert_eq!(
+            table.get_cell((0, 1)).unwrap().core,
+            Tablecellcore::Int(IBig::from(2))
+        );
     }
 
     #[test]
-    fn test_to_txt() {
-        let s = "1,2223,3\n4,5,6\n7,8,9".to_string();
-        let table = Table::from_string(s, ",", "\n");
-        table.to_txt("test.txt", ',').unwrap();
-        let s = std::fs::read_to_string("test.txt").unwrap();
-        let table = Table::from_string(s, ",", "\n");
-        println!("{:?}", table);
+    fn test_cell_padding() {
+        let mut table = Table::from_string("a 10\nbcd 2".to_string(), " ", "\n");
+        table.set_color_choice(ColorChoice::Never);
+        table.set_cell_padding(0);
+        assert_eq!(
+            table.to_string(),
+            "+---+--+\n|a  |10|\n+---+--+\n|bcd|2 |\n+---+--+\n"
+        );
+        table.set_cell_padding(2);
+        assert_eq!(
+            table.to_string(),
+            "+-------+------+\n|  a    |  10  |  \n+-------+------+\n|  bcd  |  2   |  \n+-------+------+\n"
+        );
+    }
+
+    #[test]
+    fn test_border_style() {
+        let mut table = Table::from_string("name n\na 1\nbc 22".to_string(), " ", "\n");
+        table.set_color_choice(ColorChoice::Never);
+        assert_eq!(
+            table.to_string(),
+            "+------+----+\n| name | n  | \n+------+----+\n| a    | 1  | \n+------+----+\n| bc   | 22 | \n+------+----+\n"
+        );
+        table.set_border_style(BorderStyle::Minimal);
+        assert_eq!(
+            table.to_string(),
+            "-------------\n  name   n    \n-------------\n  a      1    \n  bc     22   \n-------------\n"
+        );
+
+        // a single line has no duplicated rule
+        let mut table = Table::from_string("a b".to_string(), " ", "\n");
+        table.set_color_choice(ColorChoice::Never);
+        table.set_border_style(BorderStyle::Minimal);
+        assert_eq!(table.to_string(), "---------\n  a   b   \n---------\n");
+    }
+
+    #[test]
+    fn test_block_alignment() {
+        // the table is 9 wide
+        let mut table = Table::from_string("a b\nc d".to_string(), " ", "\n");
+        table.set_color_choice(ColorChoice::Never);
+        let indents = |table: &Table| {
+            table
+                .to_string()
+                .lines()
+                .map(|line| line.len() - line.trim_start().len())
+                .collect::<Vec<usize>>()
+        };
+        assert_eq!(indents(&table), vec![0; 5]);
+        table.set_block_alignment(Alignment::Center);
+        assert_eq!(indents(&table), vec![0; 5]);
+        table.set_target_width(Some(20));
+        assert_eq!(indents(&table), vec![5; 5]);
+        table.set_block_alignment(Alignment::Right);
+        assert_eq!(indents(&table), vec![11; 5]);
+        table.set_block_alignment(Alignment::Left);
+        assert_eq!(indents(&table), vec![0; 5]);
+        // narrower than the table
+        table.set_block_alignment(Alignment::Center);
+        table.set_target_width(Some(4));
+        assert_eq!(indents(&table), vec![0; 5]);
+    }
+
+    #[test]
+    fn test_color_choice() {
+        let mut table = Table::from_string("a 1\nb 2".to_string(), " ", "\n");
+        table.set_color_line(0, OutputColor::Red);
+        assert!(table.to_string().contains("\x1b[31ma\x1b[0m"));
+        table.set_color_choice(ColorChoice::Never);
+        let s = table.to_string();
+        assert!(!s.contains('\x1b'));
+        assert_eq!(
+            s.lines().collect::<Vec<_>>(),
+            [
+                "+---+---+",
+                "| a | 1 | ",
+                "+---+---+",
+                "| b | 2 | ",
+                "+---+---+"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_show_types() {
+        let options = ParseOptions {
+            rational: true,
+            ..ParseOptions::default()
+        };
+        let s = "a 1 2.5\nb 3/4\nc -7 true".to_string();
+        let mut table = Table::from_string_with_options(s, " ", "\n", &options);
+        table.set_color_line(0, OutputColor::Red);
+        table.set_float_format(FloatFormat::Precision(2));
+        table.set_show_types(true);
+        assert!(table.to_string().contains("\x1b[31ma<str>\x1b[0m"));
+        table.set_color_choice(ColorChoice::Never);
+        assert_eq!(
+            table.to_string().lines().collect::<Vec<_>>(),
+            [
+                "+--------+---------------+-------------+",
+                "| a<str> | 1<int>        | 2.50<float> | ",
+                "+--------+---------------+-------------+",
+                "| b<str> | 3/4<rational> |             | ",
+                "+--------+---------------+-------------+",
+                "| c<str> | -7<int>       | true<bool>  | ",
+                "+--------+---------------+-------------+"
+            ]
+        );
+
+        // the cells themselves are not changed
+        assert_eq!(
+            table.get_cell((0, 1)).unwrap().core,
+            Tablecellcore::Int(IBig::from(1))
+        );
+        table.set_show_types(false);
+        assert_eq!(
+            table.to_string().lines().nth(1),
+            Some("| a | 1   | 2.50 | ")
+        );
+    }
+
+    #[test]
+    fn test_color_legend() {
+        let mut table = Table::from_string("a 1\nb -2\nc 3".to_string(), " ", "\n");
+        assert_eq!(table.color_legend(), "");
+
+        table.set_color_line(0, OutputColor::Red);
+        table.set_color_cell((1, 1), OutputColor::Green).unwrap();
+        table.set_color_cell((2, 0), OutputColor::Red).unwrap();
+        table.lines[2].0[1].set_background(OutputColor::Yellow);
+        table.set_color_label(OutputColor::Green, "negative".to_string());
+        table.set_color_label(OutputColor::Blue, "unused".to_string());
+        assert_eq!(
+            table.color_legend(),
+            "\x1b[31mRed\x1b[0m\n\x1b[32mGreen\x1b[0m: negative\n\x1b[43mYellow background\x1b[0m\n"
+        );
+
+        table.set_color_label(OutputColor::Green, "below zero".to_string());
+        table.set_color_choice(ColorChoice::Never);
+        assert_eq!(
+            table.color_legend(),
+            "Red\nGreen: below zero\nYellow background\n"
+        );
+    }
+
+    #[test]
+    fn test_wrap_cells() {
+        let mut table = Table::from_string("abcdefghij x\nab yz".to_string(), " ", "\n");
+        table.set_max_column_width(4);
+        table.set_wrap_cells(true);
+        let s = table.to_string();
+        let border = "\x1b[90m+------\x1b[90m+----+\x1b[0m";
+        let bar = "\x1b[90m|\x1b[0m";
+        let expected = [
+            border.to_string(),
+            format!("{bar} abcd {bar} x  {bar} "),
+            format!("{bar} efgh {bar}    {bar} "),
+            format!("{bar} ij   {bar}    {bar} "),
+            border.to_string(),
+            format!("{bar} ab   {bar} yz {bar} "),
+            border.to_string(),
+        ];
+        assert_eq!(s.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_reorder_columns() {
+        let s = "1,2,3\n4,5\n7,8,9".to_string();
+        let mut table = Table::from_string(s, ",", "\n");
+        table.reorder_columns(&[2, 0, 1]);
+        let raw: Vec<String> = (0..table.len())
+            .map(|i| table.get_line(i).unwrap().to_string_raw(','))
+            .collect();
+        assert_eq!(raw, vec!["3, 1, 2, ", ", 4, 5, ", "9, 7, 8, "]);
+
+        // drop and repeat columns
+        table.reorder_columns(&[1, 1]);
+        let raw: Vec<String> = (0..table.len())
+            .map(|i| table.get_line(i).unwrap().to_string_raw(','))
+            .collect();
+        assert_eq!(raw, vec!["1, 1, ", "4, 4, ", "7, 7, "]);
+    }
+
+    #[test]
+    fn test_color_preserved_when_moving_cells() {
+        let s = "1,2,3\n4,5,6\n7,8,9".to_string();
+        let mut table = Table::from_string(s, ",", "\n");
+        table.set_color_line(0, OutputColor::Red);
+        table.set_color_column(2, OutputColor::Green);
+
+        // subtable clones the cells with their color
+        let subtable = table
+            .get_subtable_ordered((vec![0, 2], vec![2, 0]))
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", subtable.get_line(0).unwrap()),
+            "| 3<int><Green> | 1<int><Red> | "
+        );
+        assert_eq!(
+            format!("{:?}", subtable.get_line(1).unwrap()),
+            "| 9<int><Green> | 7<int><Black> | "
+        );
+
+        // cells shifted by an inserted column keep their color
+        let s = "1,2,3\n4,5,6\n7,8,9".to_string();
+        let mut table = Table::from_string(s, ",", "\n");
+        table.set_color_line(0, OutputColor::Red);
+        table.set_color_column(2, OutputColor::Green);
+        table.prepend_row_numbers(false);
+        assert_eq!(
+            format!("{:?}", table.get_line(0).unwrap()),
+            "| 1<int><Black> | 1<int><Red> | 2<int><Red> | 3<int><Green> | "
+        );
+
+        // lines moved by removal, insertion and filtering keep their color
+        let line = table.remove_line(0).unwrap();
+        table.insert_line(2, line).unwrap();
+        table.retain_rows(|line| line.get_cell(1).unwrap().to_string() != "4");
+        assert_eq!(table.len(), 2);
+        assert_eq!(
+            format!("{:?}", table.get_line(0).unwrap()),
+            "| 3<int><Black> | 7<int><Black> | 8<int><Black> | 9<int><Green> | "
+        );
+        assert_eq!(
+            format!("{:?}", table.get_line(1).unwrap()),
+            "| 1<int><Black> | 1<int><Red> | 2<int><Red> | 3<int><Green> | "
+        );
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let s = "1,a<b\n2,c\n3".to_string();
+        let mut table = Table::from_string(s, ",", "\n");
+        table.set_color_line(1, OutputColor::Red);
+        let svg = table.to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<text").count(), 5);
+        assert_eq!(svg.matches("<rect").count(), 5);
+        assert!(svg.contains("fill=\"red\">2</text>"));
+        assert!(svg.contains("fill=\"red\">c</text>"));
+        assert!(svg.contains("fill=\"black\">a&lt;b</text>"));
+
+        table.set_color_line(2, OutputColor::Ansi256(196));
+        table
+            .set_color_cell((0, 0), OutputColor::Rgb(255, 128, 0))
+            .unwrap();
+        let svg = table.to_svg();
+        assert!(svg.contains("fill=\"#ff8000\">1</text>"));
+        assert!(svg.contains("fill=\"#ff0000\">3</text>"));
+
+        // the background of a cell fills its rect
+        assert_eq!(svg.matches("fill=\"white\" stroke").count(), 5);
+        table.lines[1].0[1].set_background(OutputColor::Blue);
+        let svg = table.to_svg();
+        assert_eq!(svg.matches("fill=\"white\" stroke").count(), 4);
+        assert_eq!(svg.matches("fill=\"blue\" stroke").count(), 1);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut table = Table::from_string("a b\nc d".to_string(), " ", "\n");
+        let mut other = Table::from_string("1 2\n3 4\n5 6".to_string(), " ", "\n");
+        other.merge_cells_display(1, 0, 1).unwrap();
+        table.append(other);
+        assert_eq!(table.len(), 5);
+        assert_eq!(table.get_line(1).unwrap().to_string_raw(','), "c, d, ");
+        assert_eq!(table.get_line(2).unwrap().to_string_raw(','), "1, 2, ");
+        assert_eq!(table.get_line(4).unwrap().to_string_raw(','), "5, 6, ");
+        assert_eq!(table.spans(), &[(3, 0, 1)]);
+    }
+
+    #[test]
+    fn test_concat_right() {
+        let mut table = Table::from_string("a b\nc d\ne f".to_string(), " ", "\n");
+        let other = Table::from_string("1\n2\n3".to_string(), " ", "\n");
+        table.concat_right(&other);
         assert_eq!(table.len(), 3);
+        assert_eq!(table.get_line(0).unwrap().to_string_raw(','), "a, b, 1, ");
+        assert_eq!(table.get_line(1).unwrap().to_string_raw(','), "c, d, 2, ");
+        assert_eq!(table.get_line(2).unwrap().to_string_raw(','), "e, f, 3, ");
+        assert_eq!(table.get_cell((2, 2)).unwrap().core.type_name(), "int");
+    }
+
+    #[test]
+    fn test_concat_right_mismatched_lines() {
+        // other is shorter
+        let mut table = Table::from_string("a b\nc\ne f".to_string(), " ", "\n");
+        let other = Table::from_string("1 2".to_string(), " ", "\n");
+        table.concat_right(&other);
+        assert_eq!(
+            table.get_line(0).unwrap().to_string_raw(','),
+            "a, b, 1, 2, "
+        );
+        assert_eq!(table.get_line(1).unwrap().to_string_raw(','), "c, , , , ");
+        assert_eq!(table.get_line(2).unwrap().to_string_raw(','), "e, f, , , ");
+
+        // table is shorter
+        let mut table = Table::from_string("a b".to_string(), " ", "\n");
+        let other = Table::from_string("1\n2".to_string(), " ", "\n");
+        table.concat_right(&other);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.get_line(0).unwrap().to_string_raw(','), "a, b, 1, ");
+        assert_eq!(table.get_line(1).unwrap().to_string_raw(','), ", , 2, ");
+    }
+
+    #[test]
+    fn test_color_cells_if() {
+        let mut table = Table::from_string("1 -2 a\n-4 5 -6.5".to_string(), " ", "\n");
+        table.color_cells_if(|cell| match &cell.core {
+            Tablecellcore::Int(v) if *v < IBig::from(0) => Some(OutputColor::Red),
+            _ => None,
+        });
+        assert_eq!(
+            format!("{:?}", table.get_line(0).unwrap()),
+            "| 1<int><Black> | -2<int><Red> | a<str><Black> | "
+        );
+        assert_eq!(
+            format!("{:?}", table.get_line(1).unwrap()),
+            "| -4<int><Red> | 5<int><Black> | -6.5<float><Black> | "
+        );
+    }
+
+    #[test]
+    fn test_set_color_cell() {
+        let mut table = Table::from_string("1 2 3\n4 5 6".to_string(), " ", "\n");
+        table.set_color_cell((1, 1), OutputColor::Red).unwrap();
+        assert!(table.set_color_cell((2, 0), OutputColor::Red).is_err());
+        assert!(table.set_color_cell((0, 3), OutputColor::Red).is_err());
+        assert_eq!(
+            format!("{:?}", table.get_line(1).unwrap()),
+            "| 4<int><Black> | 5<int><Red> | 6<int><Black> | "
+        );
+        assert_eq!(
+            format!("{:?}", table.get_line(0).unwrap()),
+            "| 1<int><Black> | 2<int><Black> | 3<int><Black> | "
+        );
+    }
+
+    #[test]
+    fn test_set_cell() {
+        let mut table = Table::from_string("1 2\n3 4".to_string(), " ", "\n");
+        table.set_color_cell((0, 1), OutputColor::Red).unwrap();
+        table.lines[0].0[1].set_background(OutputColor::Blue);
+
+        table
+            .set_cell_value((0, 1), Tablecellcore::String("x".to_string()))
+            .unwrap();
+        assert_eq!(format!("{:?}", table[(0, 1)]), "x<str><Red>");
+        assert_eq!(table[(0, 1)].background, Some(OutputColor::Blue));
+
+        table.set_cell((0, 1), Tablecell::int(9)).unwrap();
+        assert_eq!(format!("{:?}", table[(0, 1)]), "9<int><Black>");
+        assert_eq!(table[(0, 1)].background, None);
+
+        assert!(table.set_cell((2, 0), Tablecell::int(1)).is_err());
+        assert!(table.set_cell((0, 2), Tablecell::int(1)).is_err());
+        assert!(table
+            .set_cell_value((1, 2), Tablecellcore::Float(1.0))
+            .is_err());
+        assert_eq!(table.dimensions(), (2, 2));
+    }
+
+    #[test]
+    fn test_find() {
+        let table = Table::from_string("a 5 12\n20 b 3.5\n7 30".to_string(), " ", "\n");
+        let greater_than_10 =
+            |cell: &Tablecell| matches!(&cell.core, Tablecellcore::Int(v) if *v > IBig::from(10));
+        assert_eq!(
+            table.find_all(greater_than_10),
+            vec![(0, 2), (1, 0), (2, 1)]
+        );
+        assert_eq!(table.find_first(greater_than_10), Some((0, 2)));
+
+        let never = |_: &Tablecell| false;
+        assert!(table.find_all(never).is_empty());
+        assert_eq!(table.find_first(never), None);
+    }
+
+    #[test]
+    fn test_find_by_regex() {
+        let mut table = Table::from_string("123 45 abc\n1234 x99 999".to_string(), " ", "\n");
+        table.set_color_line(1, OutputColor::Red);
+        let re = Regex::new(r"^\d{3}$").unwrap();
+        assert_eq!(table.find_by_regex(&re), vec![(0, 0), (1, 2)]);
+        let re = Regex::new("z").unwrap();
+        assert!(table.find_by_regex(&re).is_empty());
+    }
+
+    #[test]
+    fn test_replace_all() {
+        let mut table = Table::from_string("1,000 a-b\nx,y 12".to_string(), " ", "\n");
+        table.set_color_cell((0, 0), OutputColor::Red).unwrap();
+        // the type changes
+        let comma = Regex::new(",").unwrap();
+        assert_eq!(table.replace_all(&comma, ""), 2);
+        assert_eq!(format!("{:?}", table[(0, 0)]), "1000<int><Red>");
+        assert_eq!(format!("{:?}", table[(1, 0)]), "xy<str><Black>");
+
+        // the type is kept
+        let dash = Regex::new("(a)-(b)").unwrap();
+        assert_eq!(table.replace_all(&dash, "$2+$1"), 1);
+        assert_eq!(format!("{:?}", table[(0, 1)]), "b+a<str><Black>");
+
+        // numbers and unmatched cells are not changed
+        let digit = Regex::new("1").unwrap();
+        assert_eq!(table.replace_all(&digit, "9"), 0);
+        assert_eq!(format!("{:?}", table[(1, 1)]), "12<int><Black>");
+    }
+
+    #[test]
+    fn test_swap_rows() {
+        let s = "a b\n1 2\nx y".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        assert!(table.swap_rows(0, 2).is_ok());
+        assert_eq!(table.get_line(0).unwrap().to_string_raw(','), "x, y, ");
+        assert_eq!(table.get_line(2).unwrap().to_string_raw(','), "a, b, ");
+        assert!(table.swap_rows(1, 1).is_ok());
+        assert_eq!(table.get_line(1).unwrap().to_string_raw(','), "1, 2, ");
+        assert!(table.swap_rows(0, 3).is_err());
+        assert!(table.swap_rows(3, 0).is_err());
+        assert_eq!(table.get_line(0).unwrap().to_string_raw(','), "x, y, ");
+
+        // the merged cells move with their lines
+        table.merge_cells_display(0, 0, 1).unwrap();
+        table.merge_cells_display(1, 0, 1).unwrap();
+        table.swap_rows(0, 2).unwrap();
+        assert_eq!(table.spans(), &[(2, 0, 1), (1, 0, 1)]);
+        table.swap_rows(1, 2).unwrap();
+        assert_eq!(table.spans(), &[(1, 0, 1), (2, 0, 1)]);
+    }
+
+    #[test]
+    fn test_swap_columns() {
+        let s = "a b c\n1\nx y z".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        table.swap_columns(0, 2);
+        assert_eq!(table.get_line(0).unwrap().to_string_raw(','), "c, b, a, ");
+        assert_eq!(table.get_line(1).unwrap().to_string_raw(','), "1, ");
+        assert_eq!(table.get_line(2).unwrap().to_string_raw(','), "z, y, x, ");
+        // out of range for every line, nothing changes
+        table.swap_columns(1, 5);
+        assert_eq!(table.get_line(0).unwrap().to_string_raw(','), "c, b, a, ");
+        assert_eq!(table.get_line(2).unwrap().to_string_raw(','), "z, y, x, ");
+    }
+
+    #[test]
+    fn test_remove_column() {
+        let s = "a b c\n1\nx y z".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        let removed = table.remove_column(1);
+        assert_eq!(removed.len(), 3);
+        assert_eq!(removed[0].as_ref().unwrap().core.to_string(), "b");
+        assert!(removed[1].is_none());
+        assert_eq!(table.get_line(0).unwrap().to_string_raw(','), "a, c, ");
+        assert_eq!(table.get_line(1).unwrap().to_string_raw(','), "1, ");
+        assert_eq!(table.get_line(2).unwrap().to_string_raw(','), "x, z, ");
+    }
+
+    #[test]
+    fn test_insert_column() {
+        let s = "a b\n1 2\nx y".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        let cells = |n: usize| {
+            (0..n)
+                .map(|i| Tablecell::auto_from(i.to_string()))
+                .collect::<Vec<Tablecell>>()
+        };
+        assert!(table.insert_column(1, cells(2)).is_err());
+        assert!(table.insert_column(3, cells(3)).is_err());
+        table.insert_column(1, cells(3)).unwrap();
+        assert_eq!(table.get_line(0).unwrap().to_string_raw(','), "a, 0, b, ");
+        assert_eq!(table.get_line(1).unwrap().to_string_raw(','), "1, 1, 2, ");
+        assert_eq!(table.get_line(2).unwrap().to_string_raw(','), "x, 2, y, ");
+        table.insert_column(3, cells(3)).unwrap();
+        assert_eq!(
+            table.get_line(2).unwrap().to_string_raw(','),
+            "x, 2, y, 2, "
+        );
+    }
+
+    #[test]
+    fn test_map_column() {
+        let s = "a 1\nb 2\nc".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        table.map_column(1, |cell| match &cell.core {
+            Tablecellcore::Int(v) => Tablecell {
+                color: cell.color,
+                background: cell.background,
+                ..Tablecell::bigint(v * 2)
+            },
+            _ => cell.clone(),
+        });
+        table.map_column(0, |cell| Tablecell {
+            color: cell.color,
+            background: cell.background,
+            ..Tablecell::string(cell.core.to_string().to_uppercase())
+        });
+        assert_eq!(
+            format!("{:?}", table.get_line(0).unwrap()),
+            "| A<str><Black> | 2<int><Black> | "
+        );
+        assert_eq!(
+            format!("{:?}", table.get_line(1).unwrap()),
+            "| B<str><Black> | 4<int><Black> | "
+        );
+        assert_eq!(
+            format!("{:?}", table.get_line(2).unwrap()),
+            "| C<str><Black> | "
+        );
+    }
+
+    #[test]
+    fn test_get_column() {
+        let s = "a b c\n1\nx y".to_string();
+        let table = Table::from_string(s, " ", "\n");
+        let column: Vec<Option<String>> = table
+            .get_column(1)
+            .iter()
+            .map(|cell| cell.map(|cell| cell.core.to_string()))
+            .collect();
+        assert_eq!(
+            column,
+            vec![Some("b".to_string()), None, Some("y".to_string())]
+        );
+        assert!(table.get_column(3).iter().all(|cell| cell.is_none()));
+    }
+
+    #[test]
+    fn test_apply_subtable_spec() {
+        let s = "a b c\n1 2 3\nx y z".to_string();
+        let table = Table::from_string(s, " ", "\n");
+        let subtable = table.apply_subtable_spec((vec![], vec![3, 1, 1])).unwrap();
+        assert_eq!(subtable.len(), 3);
+        let row = |table: &Table, i: usize| table.get_line(i).unwrap().to_string_raw(',');
+        assert_eq!(row(&subtable, 0), "c, a, a, ");
+        assert_eq!(row(&subtable, 1), "3, 1, 1, ");
+        assert_eq!(row(&subtable, 2), "z, x, x, ");
+
+        let s = "a b c\n1 2 3\nx y z".to_string();
+        let table = Table::from_string(s, " ", "\n");
+        let subtable = table.apply_subtable_spec((vec![3, 1], vec![2])).unwrap();
+        assert_eq!(subtable.len(), 2);
+        assert_eq!(row(&subtable, 0), "y, ");
+        assert_eq!(row(&subtable, 1), "b, ");
+    }
+
+    #[test]
+    fn test_from_string_with_regex() {
+        let options = ParseOptions::default();
+        let table =
+            Table::from_string_with_regex("a   b\tc\n1  2 3".to_string(), r"\s+", "\n", &options)
+                .unwrap();
+        assert_eq!(
+            format!("{:?}", table.get_line(0).unwrap())
+                .matches("<str>")
+                .count(),
+            3
+        );
+        assert_eq!(table.get_cell((1, 2)).unwrap().core.to_string(), "3");
+
+        let table =
+            Table::from_string_with_regex("a;b,c\n1,2;3".to_string(), "[;,]", "\n", &options)
+                .unwrap();
         assert_eq!(table.get_line(0).unwrap().len(), 3);
         assert_eq!(table.get_line(1).unwrap().len(), 3);
-        assert_eq!(table.get_line(2).unwrap().len(), 3);
-        assert_eq!(table.get_cell((0, 0)).unwrap().to_string(), "1");
-        assert_eq!(table.get_cell((0, 1)).unwrap().to_string(), "2223");
-        assert_eq!(table.get_cell((0, 2)).unwrap().to_string(), "3");
-        assert_eq!(table.get_cell((1, 0)).unwrap().to_string(), "4");
-        assert_eq!(table.get_cell((1, 1)).unwrap().to_string(), "5");
-        assert_eq!(table.get_cell((1, 2)).unwrap().to_string(), "6");
-        assert_eq!(table.get_cell((2, 0)).unwrap().to_string(), "7");
-        assert_eq!(table.get_cell((2, 1)).unwrap().to_string(), "8");
-        assert_eq!(table.get_cell((2, 2)).unwrap().to_string(), "9");
+        assert_eq!(table.get_cell((0, 1)).unwrap().core.to_string(), "b");
+
+        let result = Table::from_string_with_regex("a(b".to_string(), "(", "\n", &options);
+        assert!(result.is_err());
+        assert!(result.err().unwrap().reason().unwrap().contains("regex"));
+
+        // the force parse rule is not dropped silently
+        let args = InputArgs {
+            force_parse: Some((
+                vec![(1, setting::ForceType::S)],
+                setting::LineColumn::Column,
+            )),
+            parse_options: ParseOptions {
+                regex_seperation: true,
+                ..options
+            },
+            ..InputArgs::default()
+        };
+        let input: &[u8] = b"1  2";
+        let error = Table::from_reader(input, r"\s+", "\n", &args).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        let error = error.into_inner().unwrap().downcast::<ArgError>().unwrap();
+        assert_eq!(error.name, ArgErrorKind::Conflicts.to_string());
+    }
+
+    #[test]
+    fn test_comment_prefix() {
+        let s = "# header comment\na 1\n  # indented comment\n\nb 2\n#c 3".to_string();
+        let options = ParseOptions {
+            comment_prefix: Some("#".to_string()),
+            ..ParseOptions::default()
+        };
+        let table = Table::from_string_with_options(s.clone(), " ", "\n", &options);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.get_cell((0, 0)).unwrap().core.to_string(), "a");
+        assert_eq!(table.get_cell((1, 0)).unwrap().core.to_string(), "b");
+        let table = Table::from_string(s, " ", "\n");
+        assert_eq!(table.len(), 5);
+    }
+
+    #[test]
+    fn test_merge_cells_display() {
+        let s = "Name Size\na b c\n1 2 3".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        assert!(table.merge_cells_display(0, 0, 2).is_err());
+        assert!(table.merge_cells_display(1, 2, 1).is_err());
+        table.merge_cells_display(1, 0, 2).unwrap();
+        assert!(table.merge_cells_display(1, 1, 1).is_err());
+        assert_eq!(table.spans(), &[(1, 0, 2)]);
+
+        let html = table.to_html_string();
+        assert_eq!(html.matches("<tr>").count(), 3);
+        assert!(html.contains("<tr><td class=\"c-black\" colspan=\"3\">a</td></tr>"));
+        assert!(html
+            .contains("<tr><td class=\"c-black\">Name</td><td class=\"c-black\">Size</td></tr>"));
+        assert!(html.contains(
+            "<tr><td class=\"c-black num\">1</td><td class=\"c-black num\">2</td><td class=\"c-black num\">3</td></tr>"
+        ));
+    }
+
+    #[test]
+    fn test_spans_follow_lines() {
+        let s = "a b c\nd e f\ng h i\nj k l".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        table.merge_cells_display(1, 0, 1).unwrap();
+        table.merge_cells_display(3, 1, 2).unwrap();
+
+        table.insert_line(0, Tableline::new()).unwrap();
+        assert_eq!(table.spans(), &[(2, 0, 1), (4, 1, 2)]);
+        table.remove_line(0).unwrap();
+        assert_eq!(table.spans(), &[(1, 0, 1), (3, 1, 2)]);
+        table.retain_rows(|line| line.get_cell(0).unwrap().core.to_string() != "a");
+        assert_eq!(table.spans(), &[(0, 0, 1), (2, 1, 2)]);
+        table.pop_line();
+        assert_eq!(table.spans(), &[(0, 0, 1)]);
+
+        // the spans of the removed lines are dropped
+        table.merge_cells_display(1, 1, 2).unwrap();
+        table.remove_lines(0, 0).unwrap();
+        assert_eq!(table.spans(), &[(0, 1, 2)]);
+        table
+            .insert_column(0, vec![Tablecell::string("x")])
+            .unwrap();
+        assert_eq!(table.spans(), &[(0, 2, 3)]);
+        table.remove_column(0);
+        assert_eq!(table.spans(), &[(0, 1, 2)]);
+        table.remove_column(2);
+        assert!(table.spans().is_empty());
+        table.merge_cells_display(0, 0, 1).unwrap();
+        table.pop_lines(5);
+        assert!(table.spans().is_empty());
+
+        // a span out of range after removing cells directly is skipped
+        let mut table = Table::from_string("a b c".to_string(), " ", "\n");
+        table.merge_cells_display(0, 1, 2).unwrap();
+        table.get_line_mut(0).unwrap().remove_cell(2).unwrap();
+        let file = std::env::temp_dir().join("str2table_test_stale_span.xlsx");
+        table.to_excel(file.to_str().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_to_latex() {
+        let s = "Name Size Note\na_b 1 50%\nc&d 2.5 #1\nx 3".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        table.merge_cells_display(3, 0, 1).unwrap();
+        let file = std::env::temp_dir().join("str2table_test_to_latex.tex");
+        table.to_latex(file.to_str().unwrap()).unwrap();
+        let latex = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(latex, table.to_latex_string());
+        let lines: Vec<&str> = latex.lines().collect();
+        // the header is string, so every column is `l`
+        assert_eq!(lines[0], r"\begin{tabular}{lll}");
+        assert_eq!(lines[1], r"\hline");
+        assert_eq!(lines[4], r"a\_b & 1 & 50\% \\");
+        assert_eq!(lines[6], r"c\&d & 2.5 & \#1 \\");
+        assert_eq!(lines[8], r"\multicolumn{2}{l}{x} &  \\");
+        assert_eq!(lines.last().unwrap(), &r"\end{tabular}");
+
+        let table = Table::from_string("1 a\n2.5 $".to_string(), " ", "\n");
+        let latex = table.to_latex_string();
+        assert!(latex.starts_with("\\begin{tabular}{rl}\n"));
+        assert!(latex.contains(r"2.5 & \$ \\"));
+        assert_eq!(
+            escape_latex(r"a\b~^{}"),
+            r"a\textbackslash{}b\textasciitilde{}\textasciicircum{}\{\}"
+        );
+    }
+
+    #[test]
+    fn test_to_html() {
+        let s = "<b> a&b\n1.5 x".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        table.set_color_line(0, OutputColor::Red);
+        table
+            .get_line_mut(1)
+            .unwrap()
+            .get_cell_mut(1)
+            .unwrap()
+            .background = Some(OutputColor::Rgb(255, 128, 0));
+        let file = std::env::temp_dir().join("str2table_test_to_html.html");
+        table.to_html(file.to_str().unwrap()).unwrap();
+        let html = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(html, table.to_html_string());
+        assert!(html.starts_with("<style>\n.num { text-align: right; }\n"));
+        assert!(html.contains(".c-red { color: red; }"));
+        assert!(html.contains(".bg-ff8000 { background-color: #ff8000; }"));
+        assert!(html.contains("<td class=\"c-red\">&lt;b&gt;</td><td class=\"c-red\">a&amp;b</td>"));
+        assert!(html.contains("<td class=\"c-black num\">1.5</td>"));
+        assert!(html.contains("<td class=\"c-black bg-ff8000\">x</td>"));
+        assert_eq!(html.matches(".c-red").count(), 1);
+    }
+
+    #[test]
+    fn test_debug_dump() {
+        let s = "1 2.5\ntrue a\"b".to_string();
+        let table = Table::from_string(s, " ", "\n");
+        let dump = table.debug_dump();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            r#"{"row":0,"col":0,"raw":"1","type":"int","value":1}"#
+        );
+        assert_eq!(
+            lines[1],
+            r#"{"row":0,"col":1,"raw":"2.5","type":"float","value":2.5}"#
+        );
+        assert_eq!(
+            lines[2],
+            r#"{"row":1,"col":0,"raw":"true","type":"bool","value":true}"#
+        );
+        assert_eq!(
+            lines[3],
+            r#"{"row":1,"col":1,"raw":"a\"b","type":"str","value":"a\"b"}"#
+        );
+    }
+
+    #[test]
+    fn test_debug_dump_raw() {
+        let s = "0x1F 1.50".to_string();
+        let mut table = Table::from_string(s, " ", "\n");
+        let dump = table.debug_dump();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(
+            lines[0],
+            r#"{"row":0,"col":0,"raw":"0x1F","type":"int","value":31}"#
+        );
+        assert_eq!(
+            lines[1],
+            r#"{"row":0,"col":1,"raw":"1.50","type":"float","value":1.5}"#
+        );
+        // the raw text is gone once the value is changed
+        table
+            .set_cell_value((0, 0), Tablecellcore::Int(IBig::from(7)))
+            .unwrap();
+        assert_eq!(
+            table.debug_dump().lines().next().unwrap(),
+            r#"{"row":0,"col":0,"raw":"7","type":"int","value":7}"#
+        );
     }
 }
@@ -15,13 +15,29 @@
 //! # Tablecell
 //! Include a struct ```Tablecell```. It attach some addition to the
 //! ```Tablecellcore```, for example color.
-use crate::setting::OutputColor;
+use crate::setting::{FloatFormat, OutputColor, ParseOptions};
 use crate::tablecellcore::Tablecellcore;
+use ibig::IBig;
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tablecell {
     pub core: Tablecellcore,
     pub color: OutputColor,
+    /// The background color, use the background of console if None
+    pub background: Option<OutputColor>,
+    /// The text the cell is parsed from, only kept if it differs from the value,
+    /// e.g. `0x1F` of the int 31
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) raw: Option<String>,
+}
+
+/// The source text is not part of the value, `0x1F` and `31` are the same cell
+impl PartialEq for Tablecell {
+    fn eq(&self, other: &Self) -> bool {
+        self.core == other.core && self.color == other.color && self.background == other.background
+    }
 }
 
 /// # TableCell
@@ -30,40 +46,89 @@ impl Tablecell {
     /// Create a new Tablecell with a string value, automatically decide its type
     /// and set color to default (black)
     pub fn auto_from(value: String) -> Self {
+        Tablecell::parsed(Tablecellcore::auto_from(&value), value)
+    }
+    /// Create a new Tablecell with a string value, automatically decide its type
+    /// with the given parse options and set color to default (black)
+    pub fn auto_from_with_options(value: String, options: &ParseOptions) -> Self {
+        let core = Tablecellcore::auto_from_with_options(&value, options);
+        Tablecell::parsed(core, value)
+    }
+    /// Create a new Tablecell forced to the given type, use ```auto_from``` if failed
+    pub fn from_type(value: String, force_type: crate::setting::ForceType) -> Self {
+        match force_type {
+            crate::setting::ForceType::S => Tablecell::force_as_string(value),
+            crate::setting::ForceType::I => Tablecell::force_as_int(value),
+            crate::setting::ForceType::F => Tablecell::force_as_float(value),
+            crate::setting::ForceType::B => Tablecell::force_as_bool(value),
+            crate::setting::ForceType::R => Tablecell::force_as_rational(value),
+        }
+    }
+
+    /// Create a new Tablecell of the value with color default (black)
+    pub(crate) fn with_core(core: Tablecellcore) -> Self {
         Tablecell {
-            core: Tablecellcore::auto_from(&value),
+            core,
             color: OutputColor::default(),
+            background: None,
+            raw: None,
         }
     }
-    ///
-    pub fn from_type(value: String, force_type: crate::setting::ForceType) -> Self {
-        Tablecell {
-            core: match force_type {
-                crate::setting::ForceType::S => Tablecellcore::force_as_string(&value),
-                crate::setting::ForceType::I => Tablecellcore::force_as_int(&value).unwrap(),
-                crate::setting::ForceType::F => Tablecellcore::force_as_float(&value).unwrap(),
-            },
 
-            color: OutputColor::default(),
+    /// Create a new Tablecell of the value parsed from the text, keep the text
+    /// if the value is shown differently
+    fn parsed(core: Tablecellcore, value: String) -> Self {
+        let raw = match core {
+            Tablecellcore::String(_) => None,
+            _ => Some(value).filter(|value| *value != core.to_string()),
+        };
+        Tablecell {
+            raw,
+            ..Tablecell::with_core(core)
         }
     }
 
+    /// Get the text the cell is parsed from, or the value shown as string if the
+    /// cell is not parsed or its value is changed
+    pub fn raw(&self) -> String {
+        self.raw.clone().unwrap_or_else(|| self.core.to_string())
+    }
+
+    /// Create a new Tablecell of int, without parsing a string
+    pub fn int(value: i64) -> Self {
+        Tablecell::with_core(Tablecellcore::Int(IBig::from(value)))
+    }
+
+    /// Create a new Tablecell of int that can be larger than i64, without parsing a string
+    pub fn bigint(value: IBig) -> Self {
+        Tablecell::with_core(Tablecellcore::Int(value))
+    }
+
+    /// Create a new Tablecell of float, without parsing a string
+    pub fn float(value: f64) -> Self {
+        Tablecell::with_core(Tablecellcore::Float(value))
+    }
+
+    /// Create a new Tablecell of string, the value is never parsed, e.g. `007` stays as it is
+    pub fn string(value: impl Into<String>) -> Self {
+        Tablecell::with_core(Tablecellcore::String(value.into()))
+    }
+
+    /// Create a new Tablecell of a missing value
+    pub fn empty() -> Self {
+        Tablecell::with_core(Tablecellcore::Empty)
+    }
+
     /// Create a new Tablecell with a string value, force it to be string and set color to default (black)
     pub fn force_as_string(value: String) -> Self {
-        Tablecell {
-            core: Tablecellcore::force_as_string(&value),
-            color: OutputColor::default(),
-        }
+        Tablecell::with_core(Tablecellcore::force_as_string(&value))
     }
 
     /// Force to convert a string to a cell of integer
     /// use ```auto_from``` if failed
     pub fn force_as_int(value: String) -> Self {
         if let Ok(cell) = Tablecellcore::force_as_int(&value) {
-            Tablecell {
-                core: cell,
-                color: OutputColor::default(),
-            }
+            Tablecell::parsed(cell, value)
         } else {
             Tablecell::auto_from(value)
         }
@@ -73,38 +138,119 @@ impl Tablecell {
     /// use ```auto_from``` if failed
     pub fn force_as_float(value: String) -> Self {
         if let Ok(cell) = Tablecellcore::force_as_float(&value) {
-            Tablecell {
-                core: cell,
-                color: OutputColor::default(),
-            }
+            Tablecell::parsed(cell, value)
+        } else {
+            Tablecell::auto_from(value)
+        }
+    }
+
+    /// Force to convert a string to a cell of bool
+    /// use ```auto_from``` if failed
+    pub fn force_as_bool(value: String) -> Self {
+        if let Ok(cell) = Tablecellcore::force_as_bool(&value) {
+            Tablecell::parsed(cell, value)
+        } else {
+            Tablecell::auto_from(value)
+        }
+    }
+
+    /// Force to convert a string to a cell of rational
+    /// use ```auto_from``` if failed
+    pub fn force_as_rational(value: String) -> Self {
+        if let Ok(cell) = Tablecellcore::force_as_rational(&value) {
+            Tablecell::parsed(cell, value)
         } else {
             Tablecell::auto_from(value)
         }
     }
 
+    /// Set the value of the cell, the text it is parsed from is dropped
+    pub fn set_core(&mut self, core: Tablecellcore) {
+        self.core = core;
+        self.raw = None;
+    }
+
     /// Set the color of the cell
     pub fn set_color(&mut self, color: OutputColor) {
         self.color = color;
     }
 
+    /// Set the background color of the cell
+    pub fn set_background(&mut self, background: OutputColor) {
+        self.background = Some(background);
+    }
+
     /// Get the length without counting the escape code for color
     pub fn len(&self) -> usize {
-        self.core.to_string().chars().count()
+        self.core.to_string().as_str().width()
+    }
+
+    /// Whether the cell is shown as nothing, e.g. an empty string or a missing value
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the length of the cell shown with the float format
+    pub fn len_with_format(&self, format: FloatFormat) -> usize {
+        self.core.to_string_with_format(format).as_str().width()
+    }
+
+    /// Get the cell with its color like ```to_string()```, but floats are shown in the format
+    pub fn to_string_with_format(&self, format: FloatFormat) -> String {
+        self.paint(self.core.to_string_with_format(format))
+    }
+
+    /// Wrap the text with the color codes of the cell
+    pub fn paint(&self, text: String) -> String {
+        let mut codes = Vec::new();
+        if let Some(code) = foreground_code(self.color) {
+            codes.push(code);
+        }
+        if let Some(background) = self.background {
+            codes.push(background_code(background));
+        }
+        if codes.is_empty() {
+            text
+        } else {
+            format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+        }
     }
 }
 
 /* --------------------------------- Display -------------------------------- */
+/// Get the escape code of the foreground color, None for black which is the default color
+fn foreground_code(color: OutputColor) -> Option<String> {
+    match color {
+        OutputColor::Black => None,
+        OutputColor::Red => Some("31".to_string()),
+        OutputColor::Green => Some("32".to_string()),
+        OutputColor::Yellow => Some("33".to_string()),
+        OutputColor::Blue => Some("34".to_string()),
+        OutputColor::White => Some("37".to_string()),
+        OutputColor::Grey => Some("90".to_string()),
+        OutputColor::Rgb(r, g, b) => Some(format!("38;2;{};{};{}", r, g, b)),
+        OutputColor::Ansi256(n) => Some(format!("38;5;{}", n)),
+    }
+}
+
+/// Get the escape code of the background color
+fn background_code(color: OutputColor) -> String {
+    match color {
+        OutputColor::Black => "40".to_string(),
+        OutputColor::Red => "41".to_string(),
+        OutputColor::Green => "42".to_string(),
+        OutputColor::Yellow => "43".to_string(),
+        OutputColor::Blue => "44".to_string(),
+        OutputColor::White => "47".to_string(),
+        OutputColor::Grey => "100".to_string(),
+        OutputColor::Rgb(r, g, b) => format!("48;2;{};{};{}", r, g, b),
+        OutputColor::Ansi256(n) => format!("48;5;{}", n),
+    }
+}
+
 impl std::fmt::Display for Tablecell {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.color {
-            OutputColor::Black => write!(f, "{}", self.core),
-            OutputColor::Red => write!(f, "\x1b[31m{}\x1b[0m", self.core),
-            OutputColor::Green => write!(f, "\x1b[32m{}\x1b[0m", self.core),
-            OutputColor::Yellow => write!(f, "\x1b[33m{}\x1b[0m", self.core),
-            OutputColor::Blue => write!(f, "\x1b[34m{}\x1b[0m", self.core),
-            OutputColor::White => write!(f, "\x1b[37m{}\x1b[0m", self.core),
-            OutputColor::Grey => write!(f, "\x1b[90m{}\x1b[0m", self.core),
-        }
+        write!(f, "{}", self.to_string_with_format(FloatFormat::default()))
     }
 }
 
@@ -113,3 +259,126 @@ impl std::fmt::Debug for Tablecell {
         write!(f, "{:?}<{}>", self.core, self.color.to_string())
     }
 }
+
+/* ------------------------------- Conversion ------------------------------- */
+
+/// The error message when a cell can't be converted to `target`
+fn conversion_error(cell: &Tablecell, target: &str) -> String {
+    format!(
+        "Cannot convert \"{}\" of type {} to {}",
+        cell.core.to_string(),
+        cell.core.type_name(),
+        target
+    )
+}
+
+impl TryFrom<&Tablecell> for i64 {
+    type Error = String;
+
+    /// Convert an int, or a float without fractional part, that fits in i64
+    fn try_from(cell: &Tablecell) -> Result<Self, Self::Error> {
+        match &cell.core {
+            Tablecellcore::Float(v)
+                if v.fract() == 0.0 && *v >= i64::MIN as f64 && *v < i64::MAX as f64 =>
+            {
+                Ok(*v as i64)
+            }
+            core => core.as_i64().ok_or_else(|| conversion_error(cell, "i64")),
+        }
+    }
+}
+
+impl TryFrom<&Tablecell> for f64 {
+    type Error = String;
+
+    /// Convert a float, or an int which may lose precision
+    fn try_from(cell: &Tablecell) -> Result<Self, Self::Error> {
+        cell.core
+            .as_f64()
+            .ok_or_else(|| conversion_error(cell, "f64"))
+    }
+}
+
+impl TryFrom<&Tablecell> for String {
+    type Error = String;
+
+    /// Convert a string, use ```to_string()``` to get any cell as string
+    fn try_from(cell: &Tablecell) -> Result<Self, Self::Error> {
+        cell.core
+            .as_str()
+            .map(|v| v.to_string())
+            .ok_or_else(|| conversion_error(cell, "String"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_color() {
+        let mut cell = Tablecell::auto_from("abc".to_string());
+        assert_eq!(cell.to_string(), "abc");
+        cell.set_color(OutputColor::Red);
+        assert_eq!(cell.to_string(), "\x1b[31mabc\x1b[0m");
+        cell.set_background(OutputColor::Blue);
+        assert_eq!(cell.to_string(), "\x1b[31;44mabc\x1b[0m");
+        assert_eq!(cell.len(), 3);
+        cell.set_color(OutputColor::Black);
+        assert_eq!(cell.to_string(), "\x1b[44mabc\x1b[0m");
+    }
+
+    #[test]
+    fn test_typed_constructors() {
+        assert_eq!(Tablecell::int(-7).core, Tablecellcore::Int(IBig::from(-7)));
+        let big = IBig::from(10u8).pow(30);
+        assert_eq!(Tablecell::bigint(big.clone()).core, Tablecellcore::Int(big));
+        assert_eq!(Tablecell::float(1e3).core, Tablecellcore::Float(1e3));
+        assert_eq!(
+            Tablecell::string("007").core,
+            Tablecellcore::String("007".to_string())
+        );
+        assert_eq!(
+            Tablecell::string("1e3".to_string()).core,
+            Tablecellcore::String("1e3".to_string())
+        );
+        assert_eq!(Tablecell::int(1).color, OutputColor::Black);
+        assert_eq!(Tablecell::int(1).background, None);
+    }
+
+    #[test]
+    fn test_display_extended_color() {
+        let mut cell = Tablecell::auto_from("abc".to_string());
+        cell.set_color(OutputColor::Rgb(255, 128, 0));
+        assert_eq!(cell.to_string(), "\x1b[38;2;255;128;0mabc\x1b[0m");
+        cell.set_color(OutputColor::Ansi256(196));
+        assert_eq!(cell.to_string(), "\x1b[38;5;196mabc\x1b[0m");
+        cell.set_background(OutputColor::Ansi256(21));
+        assert_eq!(cell.to_string(), "\x1b[38;5;196;48;5;21mabc\x1b[0m");
+        assert_eq!(cell.len(), 3);
+    }
+
+    #[test]
+    fn test_try_from() {
+        let cell = |s: &str| Tablecell::auto_from(s.to_string());
+        assert_eq!(i64::try_from(&cell("-42")), Ok(-42));
+        assert_eq!(i64::try_from(&cell("3.0")), Ok(3));
+        assert_eq!(
+            i64::try_from(&cell("3.5")),
+            Err("Cannot convert \"3.5\" of type float to i64".to_string())
+        );
+        assert!(i64::try_from(&cell("100000000000000000000")).is_err());
+        assert!(i64::try_from(&cell("abc")).is_err());
+
+        assert_eq!(f64::try_from(&cell("1.5")), Ok(1.5));
+        assert_eq!(f64::try_from(&cell("2")), Ok(2.0));
+        assert_eq!(
+            f64::try_from(&cell("true")),
+            Err("Cannot convert \"true\" of type bool to f64".to_string())
+        );
+
+        let s: Result<String, String> = (&cell("abc")).try_into();
+        assert_eq!(s, Ok("abc".to_string()));
+        assert!(String::try_from(&cell("1")).is_err());
+    }
+}
@@ -16,6 +16,7 @@
 //! Include enum called ```Tablecellcore``` represents a cell's value in a table,
 //! with some useful methods
 
+use crate::setting::{FloatFormat, ParseOptions};
 #[allow(unused_imports)]
 use ibig::{ibig, IBig};
 
@@ -24,20 +25,70 @@ use ibig::{ibig, IBig};
 ///     - String
 ///     - Int
 ///     - Float
+///     - Bool
+///     - Rational
+///     - Percent
+///     - Empty
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Tablecellcore {
     String(String),
+    #[cfg_attr(feature = "serde", serde(with = "ibig_string"))]
     Int(IBig),
     Float(f64),
+    Bool(bool),
+    /// A fraction as `(numerator, denominator)`, always reduced with a positive denominator,
+    /// so that it keeps the exact value unlike float
+    #[cfg_attr(feature = "serde", serde(with = "rational_string"))]
+    Rational(IBig, IBig),
+    /// A percentage stored as its fractional value, e.g. `50%` is 0.5
+    Percent(f64),
+    /// A missing value, i.e. null, unlike an empty string
+    Empty,
 }
 
 impl Tablecellcore {
     /// Create a new Tablecell with a string value, automatically decide its type
     pub fn auto_from(value: &String) -> Self {
-        if let Ok(v) = IBig::from_str_with_radix_prefix(value.as_str()) {
+        Self::auto_from_with_options(value, &ParseOptions::default())
+    }
+    /// Create a new Tablecell with a string value, automatically decide its type
+    /// with the given parse options
+    pub fn auto_from_with_options(value: &String, options: &ParseOptions) -> Self {
+        // a number with currency symbol or grouping seperator is parsed without them,
+        // the original string is kept if it is still not a number
+        let number = options.strip_number_format(value.as_str());
+        let number = number.as_deref().unwrap_or(value.as_str());
+        if options.empty_as_null && value.trim().is_empty() {
+            Self::Empty
+        } else if value.len() > options.max_int_digits {
+            // a huge number costs too much memory, keep it as string
+            Self::String(value.to_string())
+        } else if options.preserve_leading_zeros && has_leading_zero(number) {
+            Self::String(value.to_string())
+        } else if options.decimal_seperator != '.'
+            && options.group_seperator != Some('.')
+            && value.contains('.')
+        {
+            // `.` is not a part of numbers here, e.g. `1.234` is not 1.234 when `,` is
+            // the decimal point
+            Self::String(value.to_string())
+        } else if let Ok(v) = parse_int(number) {
             Self::Int(v)
-        } else if let Ok(v) = value.parse::<f64>() {
+        } else if let Some((numerator, denominator)) = Some(value.as_str())
+            .filter(|_| options.rational)
+            .and_then(|v| parse_rational(v).ok())
+        {
+            Self::Rational(numerator, denominator)
+        } else if let Some(v) = number
+            .strip_suffix('%')
+            .filter(|_| options.percent)
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| v.is_finite())
+        {
+            Self::Percent(v / 100.0)
+        } else if let Ok(v) = number.parse::<f64>() {
             Self::Float(v)
             // let v_f32 = value.parse::<f32>();
             // if v_f32.is_err() {
@@ -71,6 +122,8 @@ impl Tablecellcore {
             //     Self::F64(v_f64)
             // }
             // The code above is about deciding a string is f32 or f64, but is not necessary now
+        } else if let Ok(v) = value.to_lowercase().parse::<bool>() {
+            Self::Bool(v)
         } else {
             Self::String(value.to_string())
         }
@@ -81,24 +134,150 @@ impl Tablecellcore {
             Self::String(v) => v.clone(),
             Self::Int(v) => v.to_string(),
             Self::Float(v) => v.to_string(),
+            Self::Bool(v) => v.to_string(),
+            Self::Rational(numerator, denominator) => format!("{}/{}", numerator, denominator),
+            Self::Percent(v) => format!("{}%", percent_string(*v)),
+            Self::Empty => String::new(),
+        }
+    }
+    /// Convert the value to a string like ```to_string()```, but floats are shown in the format
+    pub fn to_string_with_format(&self, format: FloatFormat) -> String {
+        match (self, format) {
+            (Self::Float(v), FloatFormat::Scientific) => format!("{:e}", v),
+            (Self::Float(v), FloatFormat::Precision(precision)) => {
+                format!("{:.*}", precision, v)
+            }
+            _ => self.to_string(),
+        }
+    }
+    /// Get the short name of the type, the same as the one shown in debug mode
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "str",
+            Self::Int(_) => "int",
+            Self::Float(_) => "float",
+            Self::Bool(_) => "bool",
+            Self::Rational(_, _) => "rational",
+            Self::Percent(_) => "percent",
+            Self::Empty => "empty",
+        }
+    }
+    /// Get the value as i64, None if it is not an int or does not fit in i64
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Int(v) => i64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+    /// Get the value as f64, None if it is not a float or an int. Ints may lose precision
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Float(v) => Some(*v),
+            Self::Int(v) => Some(v.to_f64()),
+            _ => None,
+        }
+    }
+    /// Get the value as str, None if it is not a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(v) => Some(v.as_str()),
+            _ => None,
+        }
+    }
+    /// Get the value as IBig, None if it is not an int
+    pub fn as_ibig(&self) -> Option<&IBig> {
+        match self {
+            Self::Int(v) => Some(v),
+            _ => None,
         }
     }
     /// Force to convert a string to a cell of int, return Err if the Conversion failed
-    pub fn force_as_int(value: &String) -> Result<Self, ibig::error::ParseError> {
-        let v = IBig::from_str_with_radix_prefix(value.as_str())?;
+    pub fn force_as_int(value: &str) -> Result<Self, ibig::error::ParseError> {
+        let v = parse_int(value)?;
         Ok(Self::Int(v))
     }
     /// Force to convert a string to a cell of float, return Err if the Conversion failed
-    pub fn force_as_float(value: &String) -> Result<Self, std::num::ParseFloatError> {
+    pub fn force_as_float(value: &str) -> Result<Self, std::num::ParseFloatError> {
         let v = value.parse::<f64>()?;
         Ok(Self::Float(v))
     }
+    /// Force to convert a string to a cell of bool, ignoring case, return Err if the Conversion failed
+    pub fn force_as_bool(value: &str) -> Result<Self, std::str::ParseBoolError> {
+        let v = value.to_lowercase().parse::<bool>()?;
+        Ok(Self::Bool(v))
+    }
+    /// Force to convert a string like `3/4` to a cell of rational, return Err if it is not a
+    /// fraction or the denominator is zero
+    pub fn force_as_rational(value: &str) -> Result<Self, String> {
+        let (numerator, denominator) = parse_rational(value)?;
+        Ok(Self::Rational(numerator, denominator))
+    }
     /// Force to convert a string to a cell of string, won't fail
     pub fn force_as_string(value: &String) -> Self {
         Self::String(value.to_string())
     }
 }
 
+/// Check if a number starts with a zero followed by another digit, e.g. `007` or `-01.5`
+fn has_leading_zero(value: &str) -> bool {
+    let digits = value.trim_start_matches(['+', '-']).as_bytes();
+    digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()
+}
+
+/// Parse an integer in decimal, or in hexadecimal, octal or binary with a prefix `0x`, `0o`
+/// or `0b`, the prefix can be in upper case
+fn parse_int(value: &str) -> Result<IBig, ibig::error::ParseError> {
+    let (sign, rest) = match value.find(|c| c != '+' && c != '-') {
+        Some(i) => value.split_at(i),
+        None => ("", value),
+    };
+    match rest.get(..2) {
+        Some(prefix @ ("0X" | "0O" | "0B")) => {
+            let value = format!("{}{}{}", sign, prefix.to_lowercase(), &rest[2..]);
+            IBig::from_str_with_radix_prefix(value.as_str())
+        }
+        _ => IBig::from_str_with_radix_prefix(value),
+    }
+}
+
+/// Parse `numerator/denominator` to a reduced fraction with a positive denominator,
+/// return Err if it is not a fraction or the denominator is zero
+fn parse_rational(value: &str) -> Result<(IBig, IBig), String> {
+    let not_fraction = || format!("\"{}\" is not a fraction", value);
+    let (numerator, denominator) = value.split_once('/').ok_or_else(not_fraction)?;
+    let numerator = numerator.parse::<IBig>().map_err(|_| not_fraction())?;
+    let denominator = denominator.parse::<IBig>().map_err(|_| not_fraction())?;
+    if denominator == IBig::from(0u8) {
+        return Err(format!("the denominator of \"{}\" is zero", value));
+    }
+    // the denominator is not zero, so is the gcd
+    let gcd = numerator.gcd(&denominator) * denominator.signum();
+    Ok((numerator / &gcd, denominator / gcd))
+}
+
+/// Get the percentage of a fraction without `%`, e.g. `50` for 0.5.
+///
+/// The decimal point of the shortest form of the fraction is moved instead of multiplying
+/// by 100, so that `7%` is shown as `7` rather than `7.000000000000001`
+fn percent_string(fraction: f64) -> String {
+    let s = fraction.to_string();
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(s) => ("-", s),
+        None => ("", s.as_str()),
+    };
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    let frac = format!("{:0<2}", frac);
+    let int = format!("{}{}", int, &frac[..2]);
+    let int = int.trim_start_matches('0');
+    let frac = frac[2..].trim_end_matches('0');
+    let int = if int.is_empty() { "0" } else { int };
+    if frac.is_empty() {
+        format!("{}{}", sign, int)
+    } else {
+        format!("{}{}.{}", sign, int, frac)
+    }
+}
+
 /* --------------------------------- Display -------------------------------- */
 
 impl std::fmt::Display for Tablecellcore {
@@ -107,12 +286,77 @@ impl std::fmt::Display for Tablecellcore {
     }
 }
 
+/* ---------------------------------- Serde --------------------------------- */
+
+/// Serialize IBig as a decimal string, since it can be larger than any number in serde
+#[cfg(feature = "serde")]
+mod ibig_string {
+    use ibig::IBig;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &IBig, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(value.to_string().as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IBig, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<IBig>().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serialize a fraction as a string like `3/4`
+#[cfg(feature = "serde")]
+mod rational_string {
+    use ibig::IBig;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        numerator: &IBig,
+        denominator: &IBig,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(format!("{}/{}", numerator, denominator).as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<(IBig, IBig), D::Error> {
+        let s = String::deserialize(deserializer)?;
+        super::parse_rational(s.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+/* -------------------------------- PartialEq ------------------------------- */
+
+/// Two cells are equal if they have the same type and value, e.g. `1<int>` is not `1<float>`.
+/// Unlike `f64`, two NaN are equal, so that parsing the same input twice gives equal cells
+impl PartialEq for Tablecellcore {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Int(a), Self::Int(b)) => a == b,
+            (Self::Float(a), Self::Float(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Rational(a, b), Self::Rational(c, d)) => a == c && b == d,
+            (Self::Percent(a), Self::Percent(b)) => a == b,
+            (Self::Empty, Self::Empty) => true,
+            _ => false,
+        }
+    }
+}
+
 impl std::fmt::Debug for Tablecellcore {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Self::String(v) => write!(f, "{}<str>", v),
             Self::Int(v) => write!(f, "{}<int>", v),
             Self::Float(v) => write!(f, "{}<float>", v),
+            Self::Bool(v) => write!(f, "{}<bool>", v),
+            Self::Rational(numerator, denominator) => {
+                write!(f, "{}/{}<rational>", numerator, denominator)
+            }
+            Self::Percent(v) => write!(f, "{}%<percent>", percent_string(*v)),
+            Self::Empty => write!(f, "<empty>"),
         }
     }
 }
@@ -166,6 +410,156 @@ mod tests {
         assert_eq!(output, "Hello, world!<str>");
     }
 
+    #[test]
+    fn test_auto_from_max_int_digits() {
+        let value = "1".repeat(100_000);
+        let v = Tablecellcore::auto_from(&value);
+        assert!(matches!(v, Tablecellcore::String(_)));
+        assert_eq!(v.to_string(), value);
+
+        let options = ParseOptions {
+            max_int_digits: 3,
+            ..ParseOptions::default()
+        };
+        let v = Tablecellcore::auto_from_with_options(&"123".to_string(), &options);
+        assert_eq!(format!("{:?}", v), "123<int>");
+        let v = Tablecellcore::auto_from_with_options(&"1234".to_string(), &options);
+        assert_eq!(format!("{:?}", v), "1234<str>");
+    }
+
+    #[test]
+    fn test_partial_eq() {
+        let from = |s: &str| Tablecellcore::auto_from(&s.to_string());
+        assert_eq!(from("1"), from("1"));
+        assert_eq!(from("NaN"), from("NaN"));
+        assert_eq!(from("a"), Tablecellcore::String("a".to_string()));
+        assert_ne!(from("1"), from("1.0"));
+        assert_ne!(from("1"), Tablecellcore::String("1".to_string()));
+    }
+
+    #[test]
+    fn test_auto_from_bool() {
+        let v = Tablecellcore::auto_from(&"true".to_string());
+        let output = format!("{:?}", v);
+        assert_eq!(output, "true<bool>");
+        let v = Tablecellcore::auto_from(&"True".to_string());
+        let output = format!("{:?}", v);
+        assert_eq!(output, "true<bool>");
+        let v = Tablecellcore::auto_from(&"FALSE".to_string());
+        let output = format!("{:?}", v);
+        assert_eq!(output, "false<bool>");
+        let v = Tablecellcore::auto_from(&"yes".to_string());
+        let output = format!("{:?}", v);
+        assert_eq!(output, "yes<str>");
+    }
+
+    #[test]
+    fn test_auto_from_rational() {
+        // rationals are only parsed when asked, `6/8` may well be a date
+        let from = |s: &str| format!("{:?}", Tablecellcore::auto_from(&s.to_string()));
+        assert_eq!(from("3/4"), "3/4<str>");
+        assert_eq!(from("6/8"), "6/8<str>");
+
+        let options = ParseOptions {
+            rational: true,
+            ..ParseOptions::default()
+        };
+        let from = |s: &str| {
+            format!(
+                "{:?}",
+                Tablecellcore::auto_from_with_options(&s.to_string(), &options)
+            )
+        };
+        assert_eq!(from("3/4"), "3/4<rational>");
+        assert_eq!(from("-6/8"), "-3/4<rational>");
+        assert_eq!(from("3/-4"), "-3/4<rational>");
+        assert_eq!(from("4/2"), "2/1<rational>");
+        assert_eq!(from("0/5"), "0/1<rational>");
+        // zero denominator and other things with '/' are strings
+        assert_eq!(from("3/0"), "3/0<str>");
+        assert_eq!(from("1/2/3"), "1/2/3<str>");
+        assert_eq!(from("1.5/2"), "1.5/2<str>");
+        assert_eq!(from("a/b"), "a/b<str>");
+    }
+
+    #[test]
+    fn test_force_as_rational() {
+        let v = Tablecellcore::force_as_rational("10/4").unwrap();
+        assert_eq!(v.to_string(), "5/2");
+        assert_eq!(format!("{:?}", v), "5/2<rational>");
+        assert_eq!(v.type_name(), "rational");
+        assert_eq!(v, Tablecellcore::Rational(ibig!(5), ibig!(2)));
+        let v = Tablecellcore::force_as_rational("1/0");
+        assert!(v.unwrap_err().contains("zero"));
+        let v = Tablecellcore::force_as_rational("12");
+        assert!(v.is_err());
+    }
+
+    #[test]
+    fn test_auto_from_number_format() {
+        let options = ParseOptions {
+            currency_symbol: Some("$".to_string()),
+            group_seperator: Some(','),
+            ..ParseOptions::default()
+        };
+        let from = |s: &str, options: &ParseOptions| {
+            format!(
+                "{:?}",
+                Tablecellcore::auto_from_with_options(&s.to_string(), options)
+            )
+        };
+        assert_eq!(from("$1,234", &options), "1234<int>");
+        assert_eq!(from("1,234.56", &options), "1234.56<float>");
+        assert_eq!(from("-$1,234", &options), "-1234<int>");
+        assert_eq!(from("1,234$", &options), "1234<int>");
+        assert_eq!(from("$abc", &options), "$abc<str>");
+        assert_eq!(from("a,b", &options), "a,b<str>");
+        // without the options
+        assert_eq!(from("$1,234", &ParseOptions::default()), "$1,234<str>");
+
+        let european = ParseOptions {
+            group_seperator: Some(' '),
+            decimal_seperator: ',',
+            ..ParseOptions::default()
+        };
+        assert_eq!(from("1 234,56", &european), "1234.56<float>");
+        assert_eq!(from("1 234", &european), "1234<int>");
+        assert_eq!(from("1.234", &european), "1.234<str>");
+        assert_eq!(from("1.5%", &european), "1.5%<str>");
+
+        let german = ParseOptions {
+            group_seperator: Some('.'),
+            decimal_seperator: ',',
+            ..ParseOptions::default()
+        };
+        assert_eq!(from("1.234,5", &german), "1234.5<float>");
+        assert_eq!(from("1.234", &german), "1234<int>");
+        assert_eq!(from("hello world", &european), "hello world<str>");
+    }
+
+    #[test]
+    fn test_auto_from_percent() {
+        let options = ParseOptions {
+            percent: true,
+            ..ParseOptions::default()
+        };
+        let from = |s: &str| Tablecellcore::auto_from_with_options(&s.to_string(), &options);
+        assert_eq!(from("50%"), Tablecellcore::Percent(0.5));
+        assert_eq!(from("50%").to_string(), "50%");
+        assert_eq!(from("0.5%"), Tablecellcore::Percent(0.005));
+        assert_eq!(format!("{:?}", from("0.5%")), "0.5%<percent>");
+        assert_eq!(from("7%").to_string(), "7%");
+        assert_eq!(from("-12.25%").to_string(), "-12.25%");
+        assert_eq!(from("150%").to_string(), "150%");
+        assert_eq!(from("abc%"), Tablecellcore::String("abc%".to_string()));
+        assert_eq!(from("inf%"), Tablecellcore::String("inf%".to_string()));
+        // not parsed without the option
+        assert_eq!(
+            Tablecellcore::auto_from(&"50%".to_string()),
+            Tablecellcore::String("50%".to_string())
+        );
+    }
+
     #[test]
     fn test_auto_from_special() {
         let v = Tablecellcore::auto_from(&"inf".to_string());
@@ -222,55 +616,203 @@ mod tests {
         assert_eq!(v.to_string(), "Hello, world!");
     }
 
+    #[test]
+    fn test_to_string_with_format() {
+        let from = |s: &str| Tablecellcore::auto_from(&s.to_string());
+        let sci = FloatFormat::Scientific;
+        assert_eq!(
+            from("1e300").to_string(),
+            "1".to_string() + &"0".repeat(300)
+        );
+        assert_eq!(from("1e300").to_string_with_format(sci), "1e300");
+        assert_eq!(from("-2.5E+120").to_string_with_format(sci), "-2.5e120");
+        assert_eq!(from("1.5e-20").to_string(), "0.000000000000000000015");
+        assert_eq!(from("1.5e-20").to_string_with_format(sci), "1.5e-20");
+        assert_eq!(from("1e400").to_string_with_format(sci), "inf");
+
+        let precision = FloatFormat::Precision(2);
+        assert_eq!(from("3.14159").to_string_with_format(precision), "3.14");
+        assert_eq!(from("2").to_string_with_format(precision), "2");
+        assert_eq!(from("2.0").to_string_with_format(precision), "2.00");
+        assert_eq!(
+            from("3.14159").to_string_with_format(FloatFormat::Precision(0)),
+            "3"
+        );
+        assert_eq!(
+            from("3.14159").to_string_with_format(FloatFormat::Auto),
+            "3.14159"
+        );
+    }
+
     #[test]
     fn test_force_as_int() {
-        let v = Tablecellcore::force_as_int(&"123".to_string()).unwrap();
+        let v = Tablecellcore::force_as_int("123").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "123<int>");
-        let v = Tablecellcore::force_as_int(&"123456".to_string()).unwrap();
+        let v = Tablecellcore::force_as_int("123456").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "123456<int>");
-        let v = Tablecellcore::force_as_int(&"123456789".to_string()).unwrap();
+        let v = Tablecellcore::force_as_int("123456789").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "123456789<int>");
-        let v = Tablecellcore::force_as_int(&"12345678901".to_string()).unwrap();
+        let v = Tablecellcore::force_as_int("12345678901").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "12345678901<int>");
-        let v = Tablecellcore::force_as_int(&"123456789012345678901".to_string()).unwrap();
+        let v = Tablecellcore::force_as_int("123456789012345678901").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "123456789012345678901<int>");
-        let v = Tablecellcore::force_as_int(&"-123".to_string()).unwrap();
+        let v = Tablecellcore::force_as_int("-123").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "-123<int>");
-        let v = Tablecellcore::force_as_int(&"-12345".to_string()).unwrap();
+        let v = Tablecellcore::force_as_int("-12345").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "-12345<int>");
-        let v = Tablecellcore::force_as_int(&"-123456789".to_string()).unwrap();
+        let v = Tablecellcore::force_as_int("-123456789").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "-123456789<int>");
-        let v = Tablecellcore::force_as_int(&"-12345678901".to_string()).unwrap();
+        let v = Tablecellcore::force_as_int("-12345678901").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "-12345678901<int>");
-        let v = Tablecellcore::force_as_int(&"-123456789012345678901".to_string()).unwrap();
+        let v = Tablecellcore::force_as_int("-123456789012345678901").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "-123456789012345678901<int>");
-        let v = Tablecellcore::force_as_int(&"123.456".to_string());
+        let v = Tablecellcore::force_as_int("123.456");
         assert!(v.is_err());
-        let v = Tablecellcore::force_as_int(&"Hello".to_string());
+        let v = Tablecellcore::force_as_int("Hello");
         assert!(v.is_err());
     }
 
+    #[test]
+    fn test_empty_as_null() {
+        let options = ParseOptions {
+            empty_as_null: true,
+            ..ParseOptions::default()
+        };
+        for value in ["", "  "] {
+            let v = Tablecellcore::auto_from_with_options(&value.to_string(), &options);
+            assert_eq!(v, Tablecellcore::Empty);
+            assert_eq!(v.to_string(), "");
+            assert_eq!(format!("{:?}", v), "<empty>");
+        }
+        assert_eq!(
+            Tablecellcore::auto_from(&String::new()),
+            Tablecellcore::String(String::new())
+        );
+    }
+
+    #[test]
+    fn test_preserve_leading_zeros() {
+        let options = ParseOptions {
+            preserve_leading_zeros: true,
+            ..ParseOptions::default()
+        };
+        let from = |s: &str| {
+            format!(
+                "{:?}",
+                Tablecellcore::auto_from_with_options(&s.to_string(), &options)
+            )
+        };
+        assert_eq!(from("007"), "007<str>");
+        assert_eq!(from("-01.5"), "-01.5<str>");
+        assert_eq!(from("0"), "0<int>");
+        assert_eq!(from("0x10"), "16<int>");
+        assert_eq!(from("10"), "10<int>");
+        assert_eq!(from("0.5"), "0.5<float>");
+        // off by default
+        assert_eq!(
+            format!("{:?}", Tablecellcore::auto_from(&"007".to_string())),
+            "7<int>"
+        );
+    }
+
+    #[test]
+    fn test_as_native() {
+        let int = Tablecellcore::Int(IBig::from(-42));
+        assert_eq!(int.as_i64(), Some(-42));
+        assert_eq!(int.as_f64(), Some(-42.0));
+        assert_eq!(int.as_str(), None);
+        assert_eq!(int.as_ibig(), Some(&IBig::from(-42)));
+        // too big for i64, but still a lossy f64
+        let big = Tablecellcore::Int(IBig::from(10u8).pow(20));
+        assert_eq!(big.as_i64(), None);
+        assert_eq!(big.as_f64(), Some(1e20));
+
+        let float = Tablecellcore::Float(1.5);
+        assert_eq!(float.as_i64(), None);
+        assert_eq!(float.as_f64(), Some(1.5));
+        assert_eq!(float.as_str(), None);
+        assert_eq!(float.as_ibig(), None);
+
+        let string = Tablecellcore::String("abc".to_string());
+        assert_eq!(string.as_i64(), None);
+        assert_eq!(string.as_f64(), None);
+        assert_eq!(string.as_str(), Some("abc"));
+        assert_eq!(string.as_ibig(), None);
+
+        for other in [
+            Tablecellcore::Bool(true),
+            Tablecellcore::Rational(IBig::from(1), IBig::from(2)),
+            Tablecellcore::Percent(0.5),
+        ] {
+            assert_eq!(other.as_i64(), None);
+            assert_eq!(other.as_f64(), None);
+            assert_eq!(other.as_str(), None);
+            assert_eq!(other.as_ibig(), None);
+        }
+    }
+
+    #[test]
+    fn test_radix_prefix() {
+        let auto = |s: &str| format!("{:?}", Tablecellcore::auto_from(&s.to_string()));
+        let force = |s: &str| format!("{:?}", Tablecellcore::force_as_int(s).unwrap());
+        for (input, output) in [
+            ("0xFF", "255<int>"),
+            ("0xff", "255<int>"),
+            ("0XFF", "255<int>"),
+            ("0b1010", "10<int>"),
+            ("0B1010", "10<int>"),
+            ("0o17", "15<int>"),
+            ("0O17", "15<int>"),
+            ("-0x10", "-16<int>"),
+            ("+0b11", "3<int>"),
+        ] {
+            assert_eq!(auto(input), output, "auto {}", input);
+            assert_eq!(force(input), output, "force {}", input);
+        }
+        // shown in decimal
+        assert_eq!(
+            Tablecellcore::auto_from(&"0xFF".to_string()).to_string(),
+            "255"
+        );
+        // digits out of the radix are not int
+        assert_eq!(auto("0b102"), "0b102<str>");
+        assert!(Tablecellcore::force_as_int("0xG").is_err());
+    }
+
     #[test]
     fn test_force_as_float() {
         // TODO: need more test
-        let v = Tablecellcore::force_as_float(&"123.456".to_string()).unwrap();
+        let v = Tablecellcore::force_as_float("123.456").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "123.456<float>");
-        let v =
-            Tablecellcore::force_as_float(&"123.45678901234567890123456789".to_string()).unwrap();
+        let v = Tablecellcore::force_as_float("123.45678901234567890123456789").unwrap();
         let output = format!("{:?}", v);
         assert_eq!(output, "123.45678901234568<float>");
-        let v = Tablecellcore::force_as_float(&"Hello".to_string());
+        let v = Tablecellcore::force_as_float("Hello");
+        assert!(v.is_err());
+    }
+
+    #[test]
+    fn test_force_as_bool() {
+        let v = Tablecellcore::force_as_bool("TRUE").unwrap();
+        let output = format!("{:?}", v);
+        assert_eq!(output, "true<bool>");
+        let v = Tablecellcore::force_as_bool("false").unwrap();
+        let output = format!("{:?}", v);
+        assert_eq!(output, "false<bool>");
+        let v = Tablecellcore::force_as_bool("1");
+        assert!(v.is_err());
+        let v = Tablecellcore::force_as_bool("yes");
         assert!(v.is_err());
     }
 }
@@ -14,9 +14,13 @@
 
 //! # Tableline
 //! Include a vector of tablecells, representing a line of a table.
-use crate::setting::{self, InputArgs};
+use crate::error::arg_error::{ArgError, ArgErrorKind};
+use crate::setting::{self, BorderStyle, FloatFormat, InputArgs, ParseOptions};
 use crate::tablecell::Tablecell;
-#[derive(Clone)]
+use regex::Regex;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+#[derive(Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tableline(pub Vec<Tablecell>);
 
 impl Tableline {
@@ -41,12 +45,79 @@ impl Tableline {
     ///
     /// empty cells will also be ignored
     pub fn from_string(s: String, seperation: &str) -> Tableline {
-        let s = s.as_str().trim();
-        let cells: Vec<Tablecell> = s
-            .split(seperation)
-            .map(|cell| cell.trim())
-            .filter(|cell| !cell.is_empty())
-            .map(|cell| Tablecell::auto_from(cell.to_string()))
+        Tableline::from_string_with_options(s, seperation, &ParseOptions::default())
+    }
+
+    /// Parse a string to a tableline like ```from_string()``` with the given parse options
+    ///
+    /// empty cells are kept if `collapse_empty` is false, and the blank around cells is
+    /// kept if `trim` is false. The warnings of truncated cells are dropped, use
+    /// ```from_string_with_options_and_warnings()``` to get them
+    pub fn from_string_with_options(
+        s: String,
+        seperation: &str,
+        options: &ParseOptions,
+    ) -> Tableline {
+        Tableline::from_string_with_options_and_warnings(s, seperation, options, &mut Vec::new())
+    }
+
+    /// Parse a string like ```from_string_with_options()```, a warning is pushed to `warnings`
+    /// for each cell truncated to `cell_max_bytes`
+    pub fn from_string_with_options_and_warnings(
+        s: String,
+        seperation: &str,
+        options: &ParseOptions,
+        warnings: &mut Vec<ArgError>,
+    ) -> Tableline {
+        let s = trim_line(s.as_str(), options);
+        if s.trim().is_empty() {
+            return Tableline::new();
+        }
+        Tableline::from_split_cells(split_cells(s, seperation, options), options, warnings)
+    }
+
+    /// Parse a string to a tableline like ```from_string_with_options()```, but the cells are
+    /// seperated by matches of the regex. `quoted` and `escape` are not used in this mode
+    pub fn from_string_with_regex(
+        s: String,
+        seperation: &Regex,
+        options: &ParseOptions,
+    ) -> Tableline {
+        Tableline::from_string_with_regex_and_warnings(s, seperation, options, &mut Vec::new())
+    }
+
+    /// Parse a string like ```from_string_with_regex()```, a warning is pushed to `warnings`
+    /// for each cell truncated to `cell_max_bytes`
+    pub fn from_string_with_regex_and_warnings(
+        s: String,
+        seperation: &Regex,
+        options: &ParseOptions,
+        warnings: &mut Vec<ArgError>,
+    ) -> Tableline {
+        let s = trim_line(s.as_str(), options);
+        if s.trim().is_empty() {
+            return Tableline::new();
+        }
+        let cells = seperation
+            .split(s)
+            .map(|cell| (trim_cell(cell, options).to_string(), false))
+            .collect();
+        Tableline::from_split_cells(cells, options, warnings)
+    }
+
+    /// Build a tableline from the cells splitted by ```split_cells()```
+    fn from_split_cells(
+        cells: Vec<(String, bool)>,
+        options: &ParseOptions,
+        warnings: &mut Vec<ArgError>,
+    ) -> Tableline {
+        let cells: Vec<Tablecell> = cells
+            .into_iter()
+            .filter(|(cell, quoted)| *quoted || !(options.collapse_empty && cell.is_empty()))
+            .map(|(cell, _quoted)| {
+                let cell = truncate_with_options(cell.as_str(), options, warnings);
+                Tablecell::auto_from_with_options(cell.to_string(), options)
+            })
             .collect();
         Tableline(cells)
     }
@@ -67,30 +138,81 @@ impl Tableline {
         Tableline(cells)
     }
 
-    /// force parse Column
+    /// Parse a string like ```from_string_with_options()``` with the parse options of `args`,
+    /// but all the cells are forced to `force_type` if it is given, which is the type of the
+    /// line in the force parse rule. A warning is pushed to `warnings` for each cell truncated
+    /// to `cell_max_bytes`
+    pub(crate) fn from_string_with_force_parse_line_and_warnings(
+        s: String,
+        seperation: &str,
+        force_type: Option<setting::ForceType>,
+        args: &InputArgs,
+        warnings: &mut Vec<ArgError>,
+    ) -> Tableline {
+        let options = &args.parse_options;
+        let s = trim_line(s.as_str(), options);
+        if s.trim().is_empty() {
+            return Tableline::new();
+        }
+        let cells: Vec<Tablecell> = split_cells(s, seperation, options)
+            .into_iter()
+            .filter(|(cell, quoted)| *quoted || !(options.collapse_empty && cell.is_empty()))
+            .map(|(cell, _quoted)| {
+                let cell = truncate_with_options(cell.as_str(), options, warnings);
+                match force_type {
+                    Some(force_type) => Tablecell::from_type(cell.to_string(), force_type),
+                    None => Tablecell::auto_from_with_options(cell.to_string(), options),
+                }
+            })
+            .collect();
+        Tableline(cells)
+    }
+
+    /// Parse a string to a tableline like ```from_string_with_options()```, but the columns
+    /// in the force parse rule of `args` are forced to the given type. The columns are 1-based
     pub fn from_string_with_force_parse_column(
         s: String,
         seperation: &str,
         args: &InputArgs,
     ) -> Tableline {
-        let s = s.as_str().trim();
-        let cells: Vec<Tablecell> = s
-            .split(seperation)
+        Tableline::from_string_with_force_parse_column_and_warnings(
+            s,
+            seperation,
+            args,
+            &mut Vec::new(),
+        )
+    }
+
+    /// Parse a string like ```from_string_with_force_parse_column()```, a warning is pushed to
+    /// `warnings` for each cell truncated to `cell_max_bytes`
+    pub(crate) fn from_string_with_force_parse_column_and_warnings(
+        s: String,
+        seperation: &str,
+        args: &InputArgs,
+        warnings: &mut Vec<ArgError>,
+    ) -> Tableline {
+        let options = &args.parse_options;
+        let s = trim_line(s.as_str(), options);
+        if s.trim().is_empty() {
+            return Tableline::new();
+        }
+        let cells: Vec<Tablecell> = split_cells(s, seperation, options)
+            .into_iter()
+            .filter(|(cell, quoted)| *quoted || !(options.collapse_empty && cell.is_empty()))
             .enumerate()
-            .map(|(column_num, cell)| (column_num, cell.trim()))
-            .filter(|(_column_num, cell)| !cell.is_empty())
-            .map(|(column_num, cell)| {
+            .map(|(column_num, (cell, _quoted))| {
+                let cell = truncate_with_options(cell.as_str(), options, warnings);
                 let exists = args
                     .force_parse
                     .as_ref()
                     .unwrap()
                     .0
                     .iter()
-                    .find(|(a, _tmp)| *a == column_num);
+                    .find(|(a, _tmp)| *a == column_num + 1);
                 if exists.is_some() {
                     Tablecell::from_type(cell.to_string(), exists.unwrap().1)
                 } else {
-                    Tablecell::auto_from(cell.to_string())
+                    Tablecell::auto_from_with_options(cell.to_string(), options)
                 }
             })
             .collect();
@@ -99,38 +221,175 @@ impl Tableline {
 
     /// Parse a string to a tableline linke ```from_string()``` but force the cell as string
     pub fn from_string_force(s: String, seperation: &str) -> Tableline {
+        Tableline::from_string_force_and_warnings(
+            s,
+            seperation,
+            &ParseOptions::default(),
+            &mut Vec::new(),
+        )
+    }
+
+    /// Parse a string like ```from_string_force()```, but the cells are truncated to the
+    /// `cell_max_bytes` of the options, and a warning is pushed to `warnings` for each of them.
+    /// The other options are not used in this mode
+    pub fn from_string_force_and_warnings(
+        s: String,
+        seperation: &str,
+        options: &ParseOptions,
+        warnings: &mut Vec<ArgError>,
+    ) -> Tableline {
         let s = s.as_str().trim();
         let cells: Vec<Tablecell> = s
             .split(seperation)
             .map(|cell| cell.trim())
             .filter(|cell| !cell.is_empty())
-            .map(|cell| Tablecell::force_as_string(cell.to_string()))
+            .map(|cell| {
+                let cell = truncate_with_options(cell, options, warnings);
+                Tablecell::force_as_string(cell.to_string())
+            })
+            .collect();
+        Tableline(cells)
+    }
+
+    /// Parse a string to a tableline like ```from_string_force()``` with the given parse options,
+    /// but the empty and blank cells are kept as missing values. A quoted empty cell is
+    /// an empty string
+    pub fn from_string_force_with_empty(
+        s: String,
+        seperation: &str,
+        options: &ParseOptions,
+    ) -> Tableline {
+        Tableline::from_string_force_with_empty_and_warnings(
+            s,
+            seperation,
+            options,
+            &mut Vec::new(),
+        )
+    }
+
+    /// Parse a string like ```from_string_force_with_empty()```, a warning is pushed to
+    /// `warnings` for each cell truncated to `cell_max_bytes`
+    pub fn from_string_force_with_empty_and_warnings(
+        s: String,
+        seperation: &str,
+        options: &ParseOptions,
+        warnings: &mut Vec<ArgError>,
+    ) -> Tableline {
+        let s = trim_line(s.as_str(), options);
+        if s.trim().is_empty() {
+            return Tableline::new();
+        }
+        let cells: Vec<Tablecell> = split_cells(s, seperation, options)
+            .into_iter()
+            .map(|(cell, quoted)| {
+                if !quoted && cell.trim().is_empty() {
+                    Tablecell::empty()
+                } else {
+                    let cell = truncate_with_options(cell.as_str(), options, warnings);
+                    Tablecell::force_as_string(cell.to_string())
+                }
+            })
             .collect();
         Tableline(cells)
     }
 
     /// convert a tableline to string, with | as seperation and align to given width, in displau mode
-    pub fn to_string_display(&self, widths: &Vec<usize>) -> Result<String, &'static str> {
+    ///
+    /// floats are shown in `float_format`, cells wider than the width are truncated with `…`,
+    /// there are `padding` spaces on both sides of each cell and the borders are in `border_style`
+    pub fn to_string_display(
+        &self,
+        widths: &Vec<usize>,
+        float_format: FloatFormat,
+        padding: usize,
+        border_style: BorderStyle,
+    ) -> Result<String, &'static str> {
         if self.0.len() == 0 {
             return Err("Empty line");
         }
+        let pad = " ".repeat(padding);
+        let border = border_style.cell_border();
+        let seperation = format!("{}{}{}", pad, border, pad);
         let mut s = String::new();
-        s.push_str("\x1b[90m|\x1b[0m ");
+        s.push_str(format!("{}{}", border, pad).as_str());
         for (i, cell) in self.0.iter().enumerate() {
-            if widths[i] < cell.len() {
-                return Err("Width too small");
+            let mut text = cell.core.to_string_with_format(float_format);
+            let mut len = text.as_str().width();
+            if widths[i] < len {
+                if widths[i] == 0 {
+                    return Err("Width too small");
+                }
+                let mut kept = String::new();
+                len = 1;
+                for c in text.chars() {
+                    let width = c.width().unwrap_or(0);
+                    if len + width > widths[i] {
+                        break;
+                    }
+                    kept.push(c);
+                    len += width;
+                }
+                text = kept;
+                text.push('…');
             }
-            s.push_str(format!("{}", cell).as_str());
-            s.push_str(" ".repeat(widths[i] - cell.len()).as_str());
-            s.push_str(" \x1b[90m|\x1b[0m ");
+            s.push_str(cell.paint(text).as_str());
+            s.push_str(" ".repeat(widths[i] - len).as_str());
+            s.push_str(seperation.as_str());
         }
         for i in self.0.len()..widths.len() {
             s.push_str(" ".repeat(widths[i]).as_str());
-            s.push_str(" \x1b[90m|\x1b[0m ");
+            s.push_str(seperation.as_str());
         }
         Ok(s)
     }
 
+    /// convert a tableline to strings like ```to_string_display()```, but cells wider than the
+    /// width are wrapped to more lines instead of being truncated, one string for each line
+    pub fn to_string_display_wrapped(
+        &self,
+        widths: &[usize],
+        float_format: FloatFormat,
+        padding: usize,
+        border_style: BorderStyle,
+    ) -> Result<Vec<String>, &'static str> {
+        if self.0.is_empty() {
+            return Err("Empty line");
+        }
+        // split the text of each cell to pieces no wider than its column
+        let mut pieces: Vec<Vec<String>> = Vec::new();
+        for (i, cell) in self.0.iter().enumerate() {
+            if widths[i] == 0 {
+                return Err("Width too small");
+            }
+            pieces.push(wrap_to_width(
+                &cell.core.to_string_with_format(float_format),
+                widths[i],
+            ));
+        }
+        let height = pieces.iter().map(|p| p.len()).max().unwrap_or(0).max(1);
+        let pad = " ".repeat(padding);
+        let border = border_style.cell_border();
+        let seperation = format!("{}{}{}", pad, border, pad);
+        let mut lines = Vec::new();
+        for row in 0..height {
+            let mut s = String::new();
+            s.push_str(format!("{}{}", border, pad).as_str());
+            for (i, width) in widths.iter().enumerate() {
+                match (self.0.get(i), pieces.get(i).and_then(|p| p.get(row))) {
+                    (Some(cell), Some(piece)) => {
+                        let len = piece.as_str().width();
+                        s.push_str(cell.paint(piece.clone()).as_str());
+                        s.push_str(" ".repeat(width.saturating_sub(len)).as_str());
+                    }
+                    _ => s.push_str(" ".repeat(*width).as_str()),
+                }
+                s.push_str(seperation.as_str());
+            }
+            lines.push(s);
+        }
+        Ok(lines)
+    }
+
     /// convert a tableline to string, with | as seperation and align to given width, in debug mode
     pub fn to_string_debug(&self, widths: &Vec<usize>) -> Result<String, &'static str> {
         let mut s = String::new();
@@ -227,6 +486,11 @@ impl Tableline {
         self.0.len()
     }
 
+    /// Whether the line has no cell
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Get the cell at the index
     pub fn get_cell(&self, index: usize) -> Option<&Tablecell> {
         self.0.get(index)
@@ -237,6 +501,11 @@ impl Tableline {
         self.0.get_mut(index)
     }
 
+    /// Get the last cell, None if the line is empty
+    pub fn last_cell(&self) -> Option<&Tablecell> {
+        self.0.last()
+    }
+
     /// Get the cells of the line at the index range
     pub fn get_cells(&self, start: usize, end: usize) -> Option<&[Tablecell]> {
         if start >= self.0.len() || end >= self.0.len() {
@@ -254,6 +523,104 @@ impl Tableline {
     }
 }
 
+/// Trim the blank at the start and end of a line, unless `trim` of the options is false
+fn trim_line<'a>(s: &'a str, options: &ParseOptions) -> &'a str {
+    if options.trim {
+        s.trim()
+    } else {
+        s
+    }
+}
+
+/// Trim the blank around a cell, unless `trim` of the options is false
+fn trim_cell<'a>(cell: &'a str, options: &ParseOptions) -> &'a str {
+    if options.trim {
+        cell.trim()
+    } else {
+        cell
+    }
+}
+
+/// Split a line to cells with the seperation, each cell is trimmed if `trim` of the options
+///
+/// The bool is true if the cell is quoted, see ```split_special()```
+fn split_cells(s: &str, seperation: &str, options: &ParseOptions) -> Vec<(String, bool)> {
+    if options.quoted || options.escape {
+        split_special(s, seperation, options)
+    } else {
+        s.split(seperation)
+            .map(|cell| (trim_cell(cell, options).to_string(), false))
+            .collect()
+    }
+}
+
+/// Split a line to cells like ```split_cells()```
+///
+/// If `quoted`, a cell wrapped in double quotes can contain the seperation, and `""` inside
+/// the quotes is an escaped quote. The quotes are stripped and the content inside is kept verbatim
+///
+/// If `escape`, outside quotes `\` followed by the seperation is the seperation itself and
+/// `\\` is a literal backslash, any other backslash is kept verbatim
+fn split_special(s: &str, seperation: &str, options: &ParseOptions) -> Vec<(String, bool)> {
+    let escape = options.escape;
+    let finish = |cell: String, quoted: bool| {
+        if quoted {
+            (cell, true)
+        } else {
+            (trim_cell(&cell, options).to_string(), false)
+        }
+    };
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let allow_quotes = options.quoted;
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut i = 0;
+    while i < s.len() {
+        let rest = &s[i..];
+        let c = rest.chars().next().unwrap();
+        if in_quotes {
+            if rest.starts_with("\"\"") {
+                cell.push('"');
+                i += 2;
+                continue;
+            } else if c == '"' {
+                in_quotes = false;
+            } else {
+                cell.push(c);
+            }
+        } else if escape && c == '\\' {
+            let next = &rest[1..];
+            if next.starts_with('\\') {
+                cell.push('\\');
+                i += 2;
+                continue;
+            } else if !seperation.is_empty() && next.starts_with(seperation) {
+                cell.push_str(seperation);
+                i += 1 + seperation.len();
+                continue;
+            } else {
+                cell.push(c);
+            }
+        } else if !seperation.is_empty() && rest.starts_with(seperation) {
+            cells.push(finish(std::mem::take(&mut cell), quoted));
+            quoted = false;
+            i += seperation.len();
+            continue;
+        } else if allow_quotes && c == '"' && !quoted && cell.trim().is_empty() {
+            // the quote opens the cell, blank before it is ignored
+            cell.clear();
+            in_quotes = true;
+            quoted = true;
+        } else if !(quoted && c.is_whitespace()) {
+            cell.push(c);
+        }
+        i += c.len_utf8();
+    }
+    cells.push(finish(cell, quoted));
+    cells
+}
+
 /* --------------------------------- Display -------------------------------- */
 
 impl std::fmt::Display for Tableline {
@@ -283,11 +650,224 @@ impl std::fmt::Debug for Tableline {
     }
 }
 
+/// Truncate a cell to at most `max_bytes` bytes, never splitting a character
+///
+/// Return the truncated cell and a warning if it is truncated
+pub fn truncate_cell(cell: &str, max_bytes: Option<usize>) -> (&str, Option<ArgError>) {
+    match max_bytes {
+        Some(max_bytes) if cell.len() > max_bytes => {
+            let mut end = max_bytes;
+            while !cell.is_char_boundary(end) {
+                end -= 1;
+            }
+            let warning = ArgError::new(
+                ArgErrorKind::CellTruncated,
+                Some(format!("is truncated to {} bytes", end)),
+                Some(format!("cell of {} bytes", cell.len())),
+                Some("--cell-max-bytes".to_string()),
+                None,
+                None,
+            );
+            (&cell[..end], Some(warning))
+        }
+        _ => (cell, None),
+    }
+}
+
+/// Truncate a cell like ```truncate_cell()``` to `cell_max_bytes` of the options,
+/// the warning is pushed to `warnings` if it is truncated
+pub(crate) fn truncate_with_options<'a>(
+    cell: &'a str,
+    options: &ParseOptions,
+    warnings: &mut Vec<ArgError>,
+) -> &'a str {
+    let (cell, warning) = truncate_cell(cell, options.cell_max_bytes);
+    warnings.extend(warning);
+    cell
+}
+
+/// Split a text to pieces no wider than `width` in terminal columns,
+/// a character wider than `width` takes a piece alone
+fn wrap_to_width(text: &str, width: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut piece = String::new();
+    let mut piece_width = 0;
+    for c in text.chars() {
+        let c_width = c.width().unwrap_or(0);
+        if piece_width + c_width > width && !piece.is_empty() {
+            pieces.push(std::mem::take(&mut piece));
+            piece_width = 0;
+        }
+        piece.push(c);
+        piece_width += c_width;
+    }
+    if !piece.is_empty() {
+        pieces.push(piece);
+    }
+    pieces
+}
+
 /* ---------------------------------- test ---------------------------------- */
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ErrorLevel;
+    use crate::tablecellcore::Tablecellcore;
+
+    #[test]
+    fn test_cell_max_bytes() {
+        let huge = "a".repeat(1 << 20);
+        let (cell, warning) = truncate_cell(&huge, Some(16));
+        assert_eq!(cell.len(), 16);
+        let warning = warning.unwrap();
+        assert_eq!(warning.level, ErrorLevel::Warning);
+        assert_eq!(warning.error_arg.as_deref(), Some("cell of 1048576 bytes"));
+        let (cell, warning) = truncate_cell("abc", Some(16));
+        assert_eq!(cell, "abc");
+        assert!(warning.is_none());
+        // never split a character
+        let (cell, _) = truncate_cell("ab\u{4e2d}", Some(3));
+        assert_eq!(cell, "ab");
+
+        let options = ParseOptions {
+            cell_max_bytes: Some(16),
+            ..ParseOptions::default()
+        };
+        let mut warnings = Vec::new();
+        let line = Tableline::from_string_with_options_and_warnings(
+            format!("x {} 12", huge),
+            " ",
+            &options,
+            &mut warnings,
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(line.0.len(), 3);
+        assert_eq!(line.0[0].core.to_string(), "x");
+        assert_eq!(line.0[1].core.to_string(), "a".repeat(16));
+        assert_eq!(line.0[2].core.to_string(), "12");
+    }
+
+    #[test]
+    fn test_collapse_empty() {
+        let line = Tableline::from_string("a,,b".to_string(), ",");
+        assert_eq!(format!("{:?}", line), "| a<str><Black> | b<str><Black> | ");
+
+        let options = ParseOptions {
+            collapse_empty: false,
+            ..ParseOptions::default()
+        };
+        let line = Tableline::from_string_with_options("a,,b".to_string(), ",", &options);
+        assert_eq!(
+            format!("{:?}", line),
+            "| a<str><Black> | <str><Black> | b<str><Black> | "
+        );
+        let line = Tableline::from_string_with_options("  ".to_string(), ",", &options);
+        assert_eq!(line.len(), 0);
+    }
+
+    #[test]
+    fn test_trim() {
+        let line = Tableline::from_string(" a , b ".to_string(), ",");
+        assert_eq!(format!("{:?}", line), "| a<str><Black> | b<str><Black> | ");
+
+        let options = ParseOptions {
+            trim: false,
+            ..ParseOptions::default()
+        };
+        let line = Tableline::from_string_with_options(" a ,b".to_string(), ",", &options);
+        assert_eq!(
+            format!("{:?}", line),
+            "|  a <str><Black> | b<str><Black> | "
+        );
+
+        // with keep-empty-fields, `a,,b` keeps the empty middle field
+        let options = ParseOptions {
+            trim: false,
+            collapse_empty: false,
+            ..ParseOptions::default()
+        };
+        let line = Tableline::from_string_with_options("a,,b".to_string(), ",", &options);
+        assert_eq!(
+            format!("{:?}", line),
+            "| a<str><Black> | <str><Black> | b<str><Black> | "
+        );
+    }
+
+    #[test]
+    fn test_force_with_empty() {
+        let options = ParseOptions::default();
+        let line = Tableline::from_string_force_with_empty("a,,b, ,1".to_string(), ",", &options);
+        assert_eq!(
+            format!("{:?}", line),
+            "| a<str><Black> | <empty><Black> | b<str><Black> | <empty><Black> | 1<str><Black> | "
+        );
+        assert_eq!(line.0[1].core, Tablecellcore::Empty);
+
+        // a quoted empty cell is an empty string
+        let options = ParseOptions {
+            quoted: true,
+            ..ParseOptions::default()
+        };
+        let line = Tableline::from_string_force_with_empty(r#"a,"",b"#.to_string(), ",", &options);
+        assert_eq!(line.0[1].core, Tablecellcore::String(String::new()));
+    }
+
+    #[test]
+    fn test_quoted() {
+        let options = ParseOptions {
+            quoted: true,
+            ..ParseOptions::default()
+        };
+        // embedded seperation
+        let line = Tableline::from_string_with_options(r#""a,b",c"#.to_string(), ",", &options);
+        assert_eq!(
+            format!("{:?}", line),
+            "| a,b<str><Black> | c<str><Black> | "
+        );
+        let line = Tableline::from_string(r#""a,b",c"#.to_string(), ",");
+        assert_eq!(line.len(), 3);
+        // escaped quotes, blank inside quotes is kept
+        let line =
+            Tableline::from_string_with_options(r#" "say ""hi"" " , 1"#.to_string(), ",", &options);
+        assert_eq!(
+            format!("{:?}", line),
+            "| say \"hi\" <str><Black> | 1<int><Black> | "
+        );
+        // trailing quoted empty field is kept
+        let line = Tableline::from_string_with_options(r#"a,"""#.to_string(), ",", &options);
+        assert_eq!(format!("{:?}", line), "| a<str><Black> | <str><Black> | ");
+        let line = Tableline::from_string_with_options(r#"a,"",b,"#.to_string(), ",", &options);
+        assert_eq!(line.len(), 3);
+    }
+
+    #[test]
+    fn test_escape() {
+        let options = ParseOptions {
+            escape: true,
+            ..ParseOptions::default()
+        };
+        let parse = |s: &str| {
+            Tableline::from_string_with_options(s.to_string(), ",", &options)
+                .0
+                .iter()
+                .map(|cell| cell.core.to_string())
+                .collect::<Vec<String>>()
+        };
+        // escaped seperation
+        assert_eq!(parse(r"a\,b,c"), vec!["a,b", "c"]);
+        // escaped backslash, the seperation after it is not escaped
+        assert_eq!(parse(r"a\\,b"), vec![r"a\", "b"]);
+        // mixed
+        assert_eq!(parse(r"a\\\,b,\c"), vec![r"a\,b", r"\c"]);
+        // trailing lone backslash is kept
+        assert_eq!(parse(r"a,b\"), vec!["a", r"b\"]);
+        // quotes are not special without quoted
+        assert_eq!(parse(r#""a,b""#), vec![r#""a"#, r#"b""#]);
+        // without escape, backslash is a normal char
+        let line = Tableline::from_string(r"a\,b".to_string(), ",");
+        assert_eq!(line.len(), 2);
+    }
 
     #[test]
     fn test_from_string_simple() {
@@ -0,0 +1,34 @@
+use core::builder::TableBuilder;
+use core::{Tablecell, Tablecellcore};
+use ibig::IBig;
+
+#[test]
+fn test_cells_from_outside_the_crate() {
+    let big: IBig = "123456789012345678901234567890".parse().unwrap();
+    let mut table = TableBuilder::new()
+        .header(["id", "value"])
+        .row_cells([Tablecell::int(1), Tablecell::float(2.5)])
+        .row_cells([Tablecell::bigint(big), Tablecell::string("007")])
+        .row_cells([Tablecell::int(1)])
+        .build();
+    assert_eq!(
+        format!("{:?}", table.get_cell((2, 1)).unwrap().core),
+        "007<str>"
+    );
+
+    table.pad_to_rectangular(Tablecell::empty());
+    assert_eq!(table.get_line(3).unwrap().len(), 2);
+
+    table.set_cell((3, 1), Tablecell::string("x")).unwrap();
+    table
+        .set_cell_value((2, 0), Tablecellcore::String("2".to_string()))
+        .unwrap();
+    assert!(table.set_cell((9, 0), Tablecell::int(0)).is_err());
+
+    let ones = table.filter_by_column(0, &Tablecell::int(1).core);
+    assert_eq!(ones.len(), 2);
+    assert_eq!(
+        format!("{:?}", ones.get_cell((1, 1)).unwrap().core),
+        "x<str>"
+    );
+}
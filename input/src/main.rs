@@ -1,4 +1,89 @@
 pub mod read;
-fn main() {
-    println!("Hello, world!");
+
+use clap::Parser;
+use core::error::io_error::IoError;
+use core::error::{ErrorLevel, ErrorType};
+use core::export::Export;
+use core::setting::InputArgs;
+use core::sniff::sniff;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args = InputArgs::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(level) => {
+            // the last one wins if a level is given more than once
+            let code = args
+                .error_exit_code
+                .iter()
+                .rev()
+                .find(|(l, _code)| *l == level)
+                .map(|(_level, code)| *code)
+                .unwrap_or(level.exit_code());
+            ExitCode::from(code)
+        }
+    }
+}
+
+/// Print the error, return its level
+fn report_error(error: Box<dyn ErrorType>) -> ErrorLevel {
+    eprintln!("{}", error.message(ErrorLevel::Warning));
+    error.level()
+}
+
+/// Run the program, the error message is printed before returning the level of the error
+fn run(args: &InputArgs) -> Result<(), ErrorLevel> {
+    if args.sniff {
+        let s = match (&args.url, &args.input) {
+            (Some(url), _) => read::read_string_from_url(url).map_err(report_error)?,
+            (None, Some(input)) => {
+                let file = input.to_str().unwrap();
+                std::fs::read_to_string(file)
+                    .map_err(|e| report_error(Box::new(IoError::new(file, e))))?
+            }
+            (None, None) => read::read_string_from_io(),
+        };
+        println!("{}", sniff(s.as_str()));
+        return Ok(());
+    }
+
+    let mut table = match (&args.url, &args.input) {
+        (Some(url), _) => {
+            read::read_from_url(url, args.seperation.as_str(), args.end_line.as_str(), args)
+                .map_err(report_error)?
+        }
+        (None, Some(input)) => {
+            let file = input.to_str().unwrap();
+            read::read_from_file(file, args.seperation.as_str(), args.end_line.as_str(), args)
+                .map_err(report_error)?
+        }
+        (None, None) => read::read_from_io(args.seperation.as_str(), args.end_line.as_str(), args)
+            .map_err(report_error)?,
+    };
+
+    if args.debug_dump {
+        eprint!("{}", table.debug_dump());
+    }
+
+    if args.stats {
+        println!("{}", table.stats());
+        return Ok(());
+    }
+
+    // unique column, the column number is 1-based
+    if let Some(column) = args.unique {
+        table
+            .validate_no_duplicate_keys(column - 1)
+            .map_err(|e| report_error(Box::new(e)))?;
+    }
+
+    if args.row_numbers {
+        table.prepend_row_numbers(false);
+    }
+
+    table.set_float_format(args.float_format);
+    table.set_color_choice(args.color);
+    table.to_console();
+    Ok(())
 }
@@ -14,13 +14,20 @@
 
 //! # Read
 //! This module used to read input from stdin or file, and parse it to table
-use std::io::stdin;
+use std::io::{stdin, BufRead, BufReader};
 
+use core::error::arg_error::{ArgError, ArgErrorKind};
+use core::error::http_error::HttpError;
+use core::error::io_error::IoError;
+use core::error::{ErrorLevel, ErrorType};
 use core::setting::InputArgs;
+use core::setting::InputFormat;
 use core::setting::ParseMode;
 use core::table::Table;
-/// Read a table from stdin with given seperation char
-pub fn read_from_io(seperation: &str, end_line: &str, args: &InputArgs) -> Table {
+use core::Tableline;
+use regex::Regex;
+/// Read all the lines from stdin to a string
+pub fn read_string_from_io() -> String {
     let mut s = String::new();
     let lines = stdin().lines();
     for line in lines {
@@ -31,19 +38,289 @@ pub fn read_from_io(seperation: &str, end_line: &str, args: &InputArgs) -> Table
         }
         s.push('\n');
     }
-    match args.parse_mode {
-        ParseMode::A => Table::from_string(s, seperation, end_line),
-        ParseMode::S => Table::from_string_force(s, seperation, end_line),
-    }
+    s
+}
+
+/// Read a table from stdin with given seperation char
+///
+/// Return Err if stdin cannot be read or the seperation is an invalid regex
+pub fn read_from_io(
+    seperation: &str,
+    end_line: &str,
+    args: &InputArgs,
+) -> Result<Table, Box<dyn ErrorType>> {
+    read_table(stdin().lock(), "stdin", seperation, end_line, args)
 }
 
 /// Read a table from file with given seperation char
-pub fn read_from_file(file: &str, seperation: &str, end_line: &str, args: &InputArgs) -> Table {
-    let s = std::fs::read_to_string(file).unwrap();
-    match args.parse_mode {
-        ParseMode::A => Table::from_string(s, seperation, end_line),
-        ParseMode::S => Table::from_string_force(s, seperation, end_line),
+///
+/// Return Err if the file cannot be read or the seperation is an invalid regex
+pub fn read_from_file(
+    file: &str,
+    seperation: &str,
+    end_line: &str,
+    args: &InputArgs,
+) -> Result<Table, Box<dyn ErrorType>> {
+    let reader = std::fs::File::open(file)
+        .map(BufReader::new)
+        .map_err(|e| Box::new(IoError::new(file, e)) as Box<dyn ErrorType>)?;
+    read_table(reader, file, seperation, end_line, args)
+}
+
+/// Read a table from `source` in the input format of `args`, csv and tsv are parsed by
+/// ```Table::from_csv_with_warnings()``` and ```Table::from_tsv()```, others by
+/// ```Table::from_reader_with_warnings()```. The warnings of parsing are printed to stderr
+///
+/// Return Err if the reader fails, the seperation is an invalid regex, or `strict_rectangular`
+/// is set and a line has a different length from the first
+fn read_table<R: BufRead>(
+    mut reader: R,
+    source: &str,
+    seperation: &str,
+    end_line: &str,
+    args: &InputArgs,
+) -> Result<Table, Box<dyn ErrorType>> {
+    let read_all = |reader: &mut R| {
+        let mut s = String::new();
+        reader
+            .read_to_string(&mut s)
+            .map(|_| s)
+            .map_err(|e| reader_error(source, e))
+    };
+    let (table, warnings) = match args.input_format {
+        InputFormat::Csv => Table::from_csv_with_warnings(read_all(&mut reader)?.as_str(), args)
+            .map_err(|e| reader_error(source, e.into()))?,
+        InputFormat::Tsv => (Table::from_tsv(read_all(&mut reader)?), Vec::new()),
+        InputFormat::Ssv => Table::from_reader_with_warnings(reader, seperation, end_line, args)
+            .map_err(|e| reader_error(source, e))?,
+    };
+    print_warnings(warnings);
+    check_rectangular(table, args)
+}
+
+/// Print the warnings of parsing to stderr
+fn print_warnings(warnings: Vec<ArgError>) {
+    for warning in warnings {
+        eprintln!("{}", warning.message(ErrorLevel::Warning));
+    }
+}
+
+/// Convert the error of ```Table::from_reader_with_warnings()``` reading `source`, which is the ArgError
+/// inside if the seperation is an invalid regex, an IoError otherwise
+fn reader_error(source: &str, error: std::io::Error) -> Box<dyn ErrorType> {
+    if error.get_ref().is_some_and(|e| e.is::<ArgError>()) {
+        return error.into_inner().unwrap().downcast::<ArgError>().unwrap();
+    }
+    Box::new(IoError::new(source, error))
+}
+
+/// Read a table from the body of a url with given seperation char, see ```read_string_from_url()```
+///
+/// The seperation is inferred from the suffix of the url, see ```seperation_for_url()```
+pub fn read_from_url(
+    url: &str,
+    seperation: &str,
+    end_line: &str,
+    args: &InputArgs,
+) -> Result<Table, Box<dyn ErrorType>> {
+    let s = read_string_from_url(url)?;
+    parse(s, seperation_for_url(url, seperation), end_line, args)
+}
+
+/// Fetch the body of a url with a blocking http GET
+///
+/// Return Err if the request fails or the status of the response is not 200
+#[cfg(feature = "http")]
+pub fn read_string_from_url(url: &str) -> Result<String, Box<dyn ErrorType>> {
+    let http_error = |status, error: String| Box::new(HttpError::new(url, status, error));
+    let response = match ureq::get(url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(status, response)) => {
+            return Err(http_error(Some(status), response.status_text().to_string()));
+        }
+        Err(e) => return Err(http_error(None, e.to_string())),
+    };
+    if response.status() != 200 {
+        return Err(http_error(
+            Some(response.status()),
+            response.status_text().to_string(),
+        ));
     }
+    response
+        .into_string()
+        .map_err(|e| http_error(None, e.to_string()) as Box<dyn ErrorType>)
+}
+
+/// Fetch the body of a url, always Err as str2table is built without the `http` feature
+#[cfg(not(feature = "http"))]
+pub fn read_string_from_url(url: &str) -> Result<String, Box<dyn ErrorType>> {
+    Err(Box::new(HttpError::new(
+        url,
+        None,
+        "str2table is built without the `http` feature".to_string(),
+    )))
+}
+
+/// Get the seperation of the input from a url, `,` for a `.csv` url and a tab for a `.tsv` url
+/// if the seperation is the default one ` `, otherwise the seperation itself
+pub fn seperation_for_url<'a>(url: &str, seperation: &'a str) -> &'a str {
+    if seperation != " " {
+        return seperation;
+    }
+    // the query and the fragment are not part of the path
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+    if path.ends_with(".csv") {
+        ","
+    } else if path.ends_with(".tsv") {
+        "\t"
+    } else {
+        seperation
+    }
+}
+
+/// Read a file line by line with given seperation char, each line is parsed to a tableline only
+/// when it is needed, so the whole file is never kept in memory
+///
+/// Comment lines and empty lines are skipped like `read_from_file()`, but `force_parse` is not
+/// used as it needs the whole table. The warnings of a line are printed to stderr when it is
+/// parsed. Return Err if the file cannot be opened or the seperation is an invalid regex
+pub fn read_from_file_streaming<'a>(
+    file: &str,
+    seperation: &'a str,
+    end_line: &'a str,
+    args: &'a InputArgs,
+) -> Result<impl Iterator<Item = Result<Tableline, std::io::Error>> + 'a, Box<dyn ErrorType>> {
+    let reader = std::fs::File::open(file)
+        .map(BufReader::new)
+        .map_err(|e| Box::new(IoError::new(file, e)) as Box<dyn ErrorType>)?;
+    let regex = if args.parse_mode == ParseMode::A && args.parse_options.regex_seperation {
+        Some(Regex::new(seperation).map_err(|e| {
+            Box::new(ArgError::new(
+                ArgErrorKind::WrongFormat,
+                Some(format!("the regex is invalid: {}", e)),
+                Some(seperation.to_string()),
+                Some(seperation.to_string()),
+                Some((0, seperation.len())),
+                None,
+            )) as Box<dyn ErrorType>
+        })?)
+    } else {
+        None
+    };
+    let options = &args.parse_options;
+    let lines = StreamingLines {
+        reader,
+        end_line,
+        buffer: String::new(),
+        done: false,
+    };
+    Ok(lines
+        .filter(move |line| !matches!(line, Ok(line) if options.is_comment(line)))
+        .map(move |line| {
+            line.map(|line| {
+                let mut warnings = Vec::new();
+                let line = match (&args.parse_mode, &regex) {
+                    (ParseMode::A, Some(regex)) => Tableline::from_string_with_regex_and_warnings(
+                        line,
+                        regex,
+                        options,
+                        &mut warnings,
+                    ),
+                    (ParseMode::A, None) => Tableline::from_string_with_options_and_warnings(
+                        line,
+                        seperation,
+                        options,
+                        &mut warnings,
+                    ),
+                    (ParseMode::S, _) => Tableline::from_string_force_and_warnings(
+                        line,
+                        seperation,
+                        options,
+                        &mut warnings,
+                    ),
+                    (ParseMode::N, _) => Tableline::from_string_force_with_empty_and_warnings(
+                        line,
+                        seperation,
+                        options,
+                        &mut warnings,
+                    ),
+                };
+                print_warnings(warnings);
+                line
+            })
+        })
+        .filter(|line| !matches!(line, Ok(line) if line.is_empty())))
+}
+
+/// An iterator over the lines of a reader split by `end_line`, all the '\n' and '\r' not in
+/// `end_line` are removed if `end_line` does not contain '\n', like `Table::from_string()`
+struct StreamingLines<'a, R: BufRead> {
+    reader: R,
+    end_line: &'a str,
+    /// The text read but not yet returned
+    buffer: String,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for StreamingLines<'_, R> {
+    type Item = Result<String, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(index) = self.buffer.find(self.end_line) {
+                let line = self.buffer[..index].to_string();
+                self.buffer.drain(..index + self.end_line.len());
+                return Some(Ok(line));
+            }
+            if self.done {
+                if self.buffer.is_empty() {
+                    return None;
+                }
+                return Some(Ok(std::mem::take(&mut self.buffer)));
+            }
+            let mut chunk = String::new();
+            match self.reader.read_line(&mut chunk) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    if !self.end_line.contains('\n') {
+                        chunk = chunk.replace('\n', "");
+                        if !self.end_line.contains('\r') {
+                            chunk = chunk.replace('\r', "");
+                        }
+                    }
+                    self.buffer.push_str(&chunk);
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Parse the string to a table with the parse mode in `args`
+///
+/// Return Err if the seperation is an invalid regex, or `strict_rectangular` is set and a line
+/// has a different length from the first
+fn parse(
+    s: String,
+    seperation: &str,
+    end_line: &str,
+    args: &InputArgs,
+) -> Result<Table, Box<dyn ErrorType>> {
+    read_table(s.as_bytes(), "the input", seperation, end_line, args)
+}
+
+/// Return Err if `strict_rectangular` is set and a line has a different length from the first,
+/// the table itself otherwise
+fn check_rectangular(table: Table, args: &InputArgs) -> Result<Table, Box<dyn ErrorType>> {
+    if args.strict_rectangular {
+        table
+            .validate_rectangular()
+            .map_err(|e| Box::new(e) as Box<dyn ErrorType>)?;
+    }
+    Ok(table)
 }
 
 #[cfg(test)]
@@ -57,7 +334,131 @@ mod tests {
 
     #[test]
     fn test_read_from_file() {
-        let table = read_from_file("test.txt", " ", "\n", &InputArgs::default());
+        let file = concat!(env!("CARGO_MANIFEST_DIR"), "/../core/test.txt");
+        let table = read_from_file(file, " ", "\n", &InputArgs::default()).unwrap();
         println!("{:?}", table);
     }
+
+    #[test]
+    fn test_read_from_file_streaming() {
+        let file = std::env::temp_dir().join("str2table_test_streaming.txt");
+        std::fs::write(&file, "a 1\n# comment\n\nb 2\nc 3\n").unwrap();
+        let mut args = InputArgs::default();
+        args.parse_options.comment_prefix = Some("#".to_string());
+        let count = read_from_file_streaming(file.to_str().unwrap(), " ", "\n", &args)
+            .unwrap()
+            .map(|line| line.unwrap())
+            .inspect(|line| assert_eq!(line.len(), 2))
+            .count();
+        assert_eq!(count, 3);
+
+        // custom end of line across the lines of the file
+        std::fs::write(&file, "a 1;b\r\n 2;c 3").unwrap();
+        let count = read_from_file_streaming(file.to_str().unwrap(), " ", ";", &args)
+            .unwrap()
+            .count();
+        assert_eq!(count, 3);
+
+        assert!(read_from_file_streaming("nonexistent", " ", "\n", &args).is_err());
+    }
+
+    #[test]
+    fn test_strict_rectangular() {
+        let s = "a 1\nb 2 x\nc".to_string();
+        let mut args = InputArgs::default();
+        let table = parse(s.clone(), " ", "\n", &args).unwrap();
+        assert_eq!(table.len(), 3);
+
+        args.strict_rectangular = true;
+        let err = parse(s, " ", "\n", &args).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Line 2 has 3 cells, but the first line has 2."));
+        assert!(parse("a 1\nb 2".to_string(), " ", "\n", &args).is_ok());
+    }
+
+    #[test]
+    fn test_seperation_for_url() {
+        assert_eq!(seperation_for_url("http://a.com/data.csv", " "), ",");
+        assert_eq!(
+            seperation_for_url("http://a.com/DATA.TSV?x=1#top", " "),
+            "\t"
+        );
+        assert_eq!(seperation_for_url("http://a.com/data.txt", " "), " ");
+        assert_eq!(seperation_for_url("http://a.com/data.csv", ";"), ";");
+    }
+
+    /// Serve a single http response on localhost, return the url of `path` on it
+    #[cfg(feature = "http")]
+    fn serve_once(status: &str, body: &str, path: &str) -> String {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), path);
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        url
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_read_from_url() {
+        let url = serve_once("200 OK", "a,1\nb,2\n", "/data.csv");
+        let table = read_from_url(&url, " ", "\n", &InputArgs::default()).unwrap();
+        assert_eq!(table.dimensions(), (2, 2));
+
+        let url = serve_once("404 Not Found", "", "/missing.csv");
+        let err = read_from_url(&url, " ", "\n", &InputArgs::default()).unwrap_err();
+        assert!(err.to_string().contains("responds with status 404"));
+    }
+
+    #[cfg(not(feature = "http"))]
+    #[test]
+    fn test_read_from_url_without_http() {
+        let err = read_from_url("http://a.com/data.csv", " ", "\n", &InputArgs::default());
+        assert!(err
+            .unwrap_err()
+            .to_string()
+            .contains("without the `http` feature"));
+    }
+
+    #[test]
+    fn test_input_format() {
+        let s = "name,note\n\"Smith, John\",\"say \"\"hi\"\"\"\n,2\n".to_string();
+        let mut args = InputArgs {
+            input_format: InputFormat::Csv,
+            ..InputArgs::default()
+        };
+        let table = parse(s.clone(), " ", "\n", &args).unwrap();
+        assert_eq!(table.dimensions(), (3, 2));
+        assert_eq!(
+            table.get_line(1).unwrap().to_string_raw('|'),
+            "Smith, John| say \"hi\"| "
+        );
+        assert_eq!(table.get_line(2).unwrap().to_string_raw('|'), "| 2| ");
+
+        // the same input split by `,` naively
+        args.input_format = InputFormat::Ssv;
+        let table = parse(s, ",", "\n", &args).unwrap();
+        assert_eq!(table.get_line(1).unwrap().len(), 3);
+
+        args.input_format = InputFormat::Tsv;
+        let table = parse("a\t\tb\n1\t2\t3".to_string(), " ", "\n", &args).unwrap();
+        assert_eq!(table.dimensions(), (2, 3));
+    }
+
+    #[test]
+    fn test_read_from_nonexistent_file() {
+        let result = read_from_file("nonexistent", " ", "\n", &InputArgs::default());
+        assert!(result.is_err());
+    }
 }
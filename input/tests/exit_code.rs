@@ -0,0 +1,40 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn test_missing_file_exit_code() {
+    let output = Command::new(env!("CARGO_BIN_EXE_input"))
+        .args(["-i", "nonexistent"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[Fatal]"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_input"))
+        .args(["-i", "nonexistent", "--error-exit-code", "fatal=7"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(7));
+}
+
+#[test]
+fn test_unique_exit_code() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_input"))
+        .args(["--unique", "2c", "--error-exit-code", "error=5"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"a 1\nb 1\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(5));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[Error]"));
+    assert!(stderr.contains("\"1\" in lines [1, 2]"));
+}